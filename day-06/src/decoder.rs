@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use crate::marker::MarkerDetector;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameKind {
+    Packet,
+    Message,
+}
+
+// A frame runs from just after its start marker up to the beginning of the next
+// marker of the same kind (or the end of the stream), and always has some payload.
+// Offsets count signal bytes only: line endings in the stream are skipped and don't
+// count, so they match the puzzle's positions rather than raw byte positions.
+#[derive(Debug)]
+pub struct Frame {
+    pub kind: FrameKind,
+    pub marker_start: usize,
+    pub start: usize,
+    pub end: usize,
+    pub payload: Vec<u8>,
+}
+
+struct Framer {
+    kind: FrameKind,
+    window: usize,
+    detector: MarkerDetector,
+    open: Option<(usize, usize)>,
+    payload: Vec<u8>,
+}
+impl Framer {
+    fn new(kind: FrameKind, window: usize) -> Self {
+        Self {
+            kind,
+            window,
+            detector: MarkerDetector::new(window),
+            open: None,
+            payload: vec![],
+        }
+    }
+
+    // The next marker only counts once the open frame has a byte of payload, which is
+    // never part of that marker. Otherwise four fresh distinct bytes straight after a
+    // marker would start another frame, and frames would mostly be empty.
+    fn push(&mut self, byte: u8) -> Option<Frame> {
+        if self.open.is_some() {
+            self.payload.push(byte);
+        }
+        let marker_end = self.detector.push(byte);
+        if self.open.is_some() && self.payload.len() == 1 {
+            self.detector.reset();
+            return None;
+        }
+        let marker_end = marker_end?;
+        // Markers mustn't share bytes, so start the next search from scratch
+        self.detector.reset();
+        let marker_start = marker_end - self.window;
+        let closed = self.close(marker_start);
+        self.open = Some((marker_start, marker_end));
+        closed
+    }
+
+    fn close(&mut self, end: usize) -> Option<Frame> {
+        let (marker_start, start) = self.open.take()?;
+        let mut payload = std::mem::take(&mut self.payload);
+        payload.truncate(end - start);
+        Some(Frame { kind: self.kind, marker_start, start, end, payload })
+    }
+}
+
+// Decodes a datastream from any reader, a chunk at a time, yielding packet and
+// message frames as soon as they're closed off.
+pub struct Decoder<R: Read> {
+    reader: R,
+    chunk: Vec<u8>,
+    framers: Vec<Framer>,
+    ready: VecDeque<Frame>,
+    // Signal bytes seen so far
+    signal_offset: usize,
+    finished: bool,
+}
+impl<R: Read> Decoder<R> {
+    pub fn new(reader: R, chunk_size: usize) -> Self {
+        Self::with_windows(reader, chunk_size, 4, 14)
+    }
+
+    pub fn with_windows(reader: R, chunk_size: usize, packet_window: usize, message_window: usize) -> Self {
+        Self {
+            reader,
+            chunk: vec![0; chunk_size.max(1)],
+            framers: vec![
+                Framer::new(FrameKind::Packet, packet_window),
+                Framer::new(FrameKind::Message, message_window),
+            ],
+            ready: VecDeque::new(),
+            signal_offset: 0,
+            finished: false,
+        }
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let read = loop {
+            match self.reader.read(&mut self.chunk) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result?,
+            }
+        };
+
+        if read == 0 {
+            self.finished = true;
+            let end = self.signal_offset;
+            // A marker right at the end opens a frame with nothing in it, which isn't a frame
+            self.ready.extend(self.framers.iter_mut().filter_map(|framer| framer.close(end)).filter(|frame| !frame.payload.is_empty()));
+            return Ok(());
+        }
+
+        for &byte in &self.chunk[..read] {
+            // Line endings aren't part of the signal
+            if byte == b'\n' || byte == b'\r' {
+                continue;
+            }
+            self.signal_offset += 1;
+            for framer in self.framers.iter_mut() {
+                if let Some(frame) = framer.push(byte) {
+                    self.ready.push_back(frame);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+impl<R: Read> Iterator for Decoder<R> {
+    type Item = io::Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.ready.is_empty() && !self.finished {
+            if let Err(e) = self.fill() {
+                self.finished = true;
+                return Some(Err(e));
+            }
+        }
+        self.ready.pop_front().map(Ok)
+    }
+}
@@ -1,23 +1,42 @@
-use std::fs;
-use fancy_regex::Regex;
-use itertools::Itertools;
+mod marker;
+mod decoder;
+use std::{env, fs, io};
+use decoder::{Decoder, Frame, FrameKind};
+use marker::marker_positions;
 
 fn load_data() -> String {
     fs::read_to_string("data/data.txt").expect("Should have read")
 }
 
-fn part_one(data: &String) -> usize {
-    let pattern = Regex::new(r"(.)(?!.{,2}\1)(.)(?!.?\2)(.)(?!\3).").unwrap();
-    pattern.find(data).unwrap().unwrap().end()
+fn first_marker(data: &str, window: usize) -> usize {
+    *marker_positions(data.trim_end().as_bytes(), window).first().expect("No marker found")
 }
 
-fn part_two(data: &String) -> usize {
-    data.as_bytes().windows(14).position(|window| {
-        window.into_iter().unique().count() == 14
-    }).unwrap() + 14
+fn part_one(data: &str) -> usize {
+    first_marker(data, 4)
+}
+
+fn part_two(data: &str) -> usize {
+    first_marker(data, 14)
+}
+
+fn print_frame(frame: &Frame) {
+    let kind = match frame.kind {
+        FrameKind::Packet => "packet",
+        FrameKind::Message => "message",
+    };
+    println!("{} marker at {}, payload {}..{}: {}", kind, frame.marker_start, frame.start, frame.end, String::from_utf8_lossy(&frame.payload));
 }
 
 fn main() {
+    // `--stream` decodes stdin as a live feed instead of solving the puzzle
+    if env::args().any(|arg| arg == "--stream") {
+        for frame in Decoder::new(io::stdin().lock(), 64) {
+            print_frame(&frame.expect("Should have read stream"));
+        }
+        return;
+    }
+
     let data = load_data();
     println!("Packet begin: {}", part_one(&data));
     println!("Message begin: {}", part_two(&data));
@@ -0,0 +1,57 @@
+use std::collections::VecDeque;
+
+// Rolling detector for runs of `window` distinct bytes. Feed it one byte at a time;
+// it keeps a count of each byte in the current window, so each push is O(1).
+pub struct MarkerDetector {
+    window: usize,
+    counts: [usize; 256],
+    distinct: usize,
+    recent: VecDeque<u8>,
+    offset: usize,
+}
+impl MarkerDetector {
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "Marker window must be at least one byte");
+        Self {
+            window,
+            counts: [0; 256],
+            distinct: 0,
+            recent: VecDeque::with_capacity(window + 1),
+            offset: 0,
+        }
+    }
+
+    // Push the next byte. Returns the marker position (count of bytes read up to and
+    // including the end of the marker) if the last `window` bytes are all different.
+    pub fn push(&mut self, byte: u8) -> Option<usize> {
+        self.offset += 1;
+        self.recent.push_back(byte);
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 1 {
+            self.distinct += 1;
+        }
+
+        if self.recent.len() > self.window {
+            let dropped = self.recent.pop_front().unwrap();
+            self.counts[dropped as usize] -= 1;
+            if self.counts[dropped as usize] == 0 {
+                self.distinct -= 1;
+            }
+        }
+
+        if self.distinct == self.window { Some(self.offset) } else { None }
+    }
+
+    // Forget the current window, so the next marker has to be built from fresh bytes
+    pub fn reset(&mut self) {
+        self.counts = [0; 256];
+        self.distinct = 0;
+        self.recent.clear();
+    }
+}
+
+// Every position at which a marker of `window` distinct bytes ends
+pub fn marker_positions(data: &[u8], window: usize) -> Vec<usize> {
+    let mut detector = MarkerDetector::new(window);
+    data.iter().filter_map(|&byte| detector.push(byte)).collect()
+}
@@ -1,51 +1,232 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// Values stored in an ArenaTree are identified by a key, so the tree can find an
+// existing node without scanning the arena.
+pub trait Keyed {
+    type Key: Hash + Eq + Clone;
+    fn key(&self) -> Self::Key;
+}
+
+// Node ids are arena positions and stay valid for the life of the tree. Removed
+// nodes leave a hole rather than shifting everything after them.
 #[derive(Debug)]
-pub struct ArenaTree<T> 
+pub struct ArenaTree<T>
 where
-    T: PartialEq
+    T: Keyed
 {
-    pub arena: Vec<Node<T>>,
+    arena: Vec<Option<Node<T>>>,
+    index: HashMap<T::Key, usize>,
 }
 impl<T> ArenaTree<T>
 where
-    T: PartialEq
+    T: Keyed
 {
     pub fn new() -> Self {
-        Self { arena: vec![] }
+        Self { arena: vec![], index: HashMap::new() }
     }
+
+    // Find the node holding `val`'s key, or add `val` as a new detached node
     pub fn node(&mut self, val: T) -> usize {
         //first see if it exists
-        for node in &self.arena {
-            if node.val == val {
-                return node.idx;
-            }
+        let key = val.key();
+        if let Some(&idx) = self.index.get(&key) {
+            return idx;
         }
         // Otherwise, add new node
         let idx = self.arena.len();
-        self.arena.push(Node::new(idx, val));
+        self.arena.push(Some(Node::new(val)));
+        self.index.insert(key, idx);
         idx
     }
+
+    pub fn find(&self, key: &T::Key) -> Option<usize> {
+        self.index.get(key).copied()
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        matches!(self.arena.get(idx), Some(Some(_)))
+    }
+
+    // Ids of every live node, in insertion order
+    pub fn ids(&self) -> impl Iterator<Item = usize> + '_ {
+        self.arena.iter().enumerate().filter_map(|(idx, node)| node.as_ref().map(|_| idx))
+    }
+
+    fn get_node(&self, idx: usize) -> &Node<T> {
+        self.arena[idx].as_ref().expect("Node has been removed")
+    }
+
+    fn get_node_mut(&mut self, idx: usize) -> &mut Node<T> {
+        self.arena[idx].as_mut().expect("Node has been removed")
+    }
+
+    pub fn get(&self, idx: usize) -> &T {
+        &self.get_node(idx).val
+    }
+
+    // Changing the value's key through here won't re-index it
+    pub fn get_mut(&mut self, idx: usize) -> &mut T {
+        &mut self.get_node_mut(idx).val
+    }
+
+    pub fn parent(&self, idx: usize) -> Option<usize> {
+        self.get_node(idx).parent
+    }
+
+    pub fn children(&self, idx: usize) -> &[usize] {
+        &self.get_node(idx).children
+    }
+
+    // Make `child` the last child of `parent`, moving it from wherever it was
+    pub fn attach(&mut self, parent: usize, child: usize) {
+        if self.ancestors(parent).chain([parent]).any(|idx| idx == child) {
+            panic!("Can't attach a node beneath itself");
+        }
+        self.detach(child);
+        self.get_node_mut(child).parent = Some(parent);
+        self.get_node_mut(parent).children.push(child);
+    }
+
+    // Cut `idx` (and everything under it) loose from its parent
+    pub fn detach(&mut self, idx: usize) {
+        if let Some(parent) = self.get_node_mut(idx).parent.take() {
+            self.get_node_mut(parent).children.retain(|&child| child != idx);
+        }
+    }
+
+    // Delete `idx` and everything under it, returning the values in pre-order
+    pub fn remove_subtree(&mut self, idx: usize) -> Vec<T> {
+        self.detach(idx);
+        let doomed: Vec<usize> = self.pre_order(idx).collect();
+        doomed.into_iter().map(|idx| {
+            let node = self.arena[idx].take().unwrap();
+            self.index.remove(&node.val.key());
+            node.val
+        }).collect()
+    }
+
+    // `idx` first, then each child's subtree in turn
+    pub fn pre_order(&self, idx: usize) -> PreOrder<'_, T> {
+        PreOrder { tree: self, stack: vec![idx] }
+    }
+
+    // Each child's subtree in turn, then `idx` last
+    pub fn post_order(&self, idx: usize) -> PostOrder<'_, T> {
+        PostOrder { tree: self, stack: vec![(idx, false)] }
+    }
+
+    // Everything under `idx`, in pre-order, not including `idx` itself
+    pub fn descendants(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        self.pre_order(idx).skip(1)
+    }
+
+    // Parent, grandparent and so on up to the top of the tree
+    pub fn ancestors(&self, idx: usize) -> Ancestors<'_, T> {
+        Ancestors { tree: self, current: self.parent(idx) }
+    }
+
+    // Steps from the top of the tree; a node with no parent is at depth 0
+    pub fn depth(&self, idx: usize) -> usize {
+        self.ancestors(idx).count()
+    }
+
+    // Deepest node that has both `a` and `b` beneath it (or is one of them).
+    // None if they're in separate trees.
+    pub fn lowest_common_ancestor(&self, a: usize, b: usize) -> Option<usize> {
+        let (mut a, mut b) = (a, b);
+        let (mut depth_a, mut depth_b) = (self.depth(a), self.depth(b));
+        while depth_a > depth_b {
+            a = self.parent(a)?;
+            depth_a -= 1;
+        }
+        while depth_b > depth_a {
+            b = self.parent(b)?;
+            depth_b -= 1;
+        }
+        while a != b {
+            a = self.parent(a)?;
+            b = self.parent(b)?;
+        }
+        Some(a)
+    }
+
+    // Combine each node with the results for its children, bottom up. `f` sees
+    // every node in the subtree exactly once; the result for `idx` is returned.
+    pub fn fold<A, F>(&self, idx: usize, mut f: F) -> A
+    where
+        F: FnMut(usize, &T, Vec<A>) -> A
+    {
+        let mut results: Vec<A> = vec![];
+        for node in self.post_order(idx) {
+            let children = results.split_off(results.len() - self.children(node).len());
+            results.push(f(node, self.get(node), children));
+        }
+        results.pop().unwrap()
+    }
 }
 
 #[derive(Debug)]
-pub struct Node<T>
-where
-    T: PartialEq
+struct Node<T>
 {
-    pub idx: usize,
-    pub val: T,
-    pub parent: Option<usize>,
-    pub children: Vec<usize>,
+    val: T,
+    parent: Option<usize>,
+    children: Vec<usize>,
 }
 impl<T> Node<T>
-where
-    T: PartialEq
 {
-    pub fn new(idx: usize, val: T) -> Self {
+    fn new(val: T) -> Self {
         Self {
-            idx,
             val,
             parent: None,
             children: vec![],
         }
     }
 }
+
+pub struct PreOrder<'a, T: Keyed> {
+    tree: &'a ArenaTree<T>,
+    stack: Vec<usize>,
+}
+impl<T: Keyed> Iterator for PreOrder<'_, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let idx = self.stack.pop()?;
+        self.stack.extend(self.tree.children(idx).iter().rev());
+        Some(idx)
+    }
+}
+
+pub struct PostOrder<'a, T: Keyed> {
+    tree: &'a ArenaTree<T>,
+    stack: Vec<(usize, bool)>,
+}
+impl<T: Keyed> Iterator for PostOrder<'_, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            let (idx, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(idx);
+            }
+            self.stack.push((idx, true));
+            self.stack.extend(self.tree.children(idx).iter().rev().map(|&child| (child, false)));
+        }
+    }
+}
+
+pub struct Ancestors<'a, T: Keyed> {
+    tree: &'a ArenaTree<T>,
+    current: Option<usize>,
+}
+impl<T: Keyed> Iterator for Ancestors<'_, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let idx = self.current?;
+        self.current = self.tree.parent(idx);
+        Some(idx)
+    }
+}
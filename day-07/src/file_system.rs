@@ -0,0 +1,109 @@
+use crate::arena_tree::{ArenaTree, Keyed};
+
+pub struct ElfFile {
+    pub name: String,
+    pub size: usize
+}
+impl ElfFile {
+    pub fn new(name: String, size: usize) -> Self {
+        Self { name, size }
+    }
+}
+pub struct ElfDir {
+    pub name: String,
+    pub size: usize
+}
+impl ElfDir {
+    pub fn new(name: String) -> Self {
+        Self { name, size: 0 }
+    }
+}
+
+pub enum FSObject {
+    File(ElfFile),
+    Dir(ElfDir)
+}
+impl FSObject {
+    pub fn size(&self) -> usize {
+        match self {
+            Self::File(f) => f.size,
+            Self::Dir(d) => d.size
+        }
+    }
+    pub fn size_mut(&mut self) -> &mut usize {
+        match self {
+            Self::File(f) => &mut f.size,
+            Self::Dir(d) => &mut d.size
+        }
+    }
+    pub fn name(&self) -> &String {
+        match self {
+            Self::File(f) => &f.name,
+            Self::Dir(d) => &d.name
+        }
+    }
+    // Last segment of the path; the root is just "/"
+    pub fn base_name(&self) -> &str {
+        let path = self.name().trim_end_matches('/');
+        match path.rsplit_once('/') {
+            Some((_, base)) => base,
+            None => "/",
+        }
+    }
+}
+
+// Nodes are keyed by full path; directory paths end in '/', so they can't clash with files
+impl Keyed for FSObject {
+    type Key = String;
+    fn key(&self) -> String {
+        self.name().clone()
+    }
+}
+
+pub type FileSystem = ArenaTree<FSObject>;
+
+impl FileSystem {
+    // Find or create `val` as a child of `parent`. A node that's already in the tree stays put.
+    pub fn add_child(&mut self, parent: usize, val: FSObject) -> usize {
+        let child = self.node(val);
+        if child != parent && self.parent(child).is_none() {
+            self.attach(parent, child);
+        }
+        child
+    }
+
+    // Delete the node at `path` and everything under it; directory sizes above it shrink to match
+    pub fn remove(&mut self, path: &str) -> bool {
+        match self.find(&path.to_string()) {
+            Some(idx) if idx != 0 => {
+                self.remove_subtree(idx);
+                compute_dir_sizes(self);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+// Fill in every directory's size from the files below it. Done once the tree is
+// complete, so listing a directory twice can't count anything twice.
+pub fn compute_dir_sizes(file_system: &mut FileSystem) {
+    if !file_system.contains(0) {
+        return;
+    }
+
+    let mut dir_sizes = vec![];
+    file_system.fold(0, |idx, val, children: Vec<usize>| {
+        match val {
+            FSObject::File(file) => file.size,
+            FSObject::Dir(_) => {
+                let size = children.iter().sum();
+                dir_sizes.push((idx, size));
+                size
+            }
+        }
+    });
+    for (idx, size) in dir_sizes {
+        *file_system.get_mut(idx).size_mut() = size;
+    }
+}
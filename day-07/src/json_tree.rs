@@ -0,0 +1,63 @@
+use json::{self, object, JsonValue};
+use crate::file_system::{compute_dir_sizes, ElfDir, ElfFile, FSObject, FileSystem};
+
+// Nested form: every node has a name, type and size; directories also have children.
+pub fn to_json(file_system: &FileSystem) -> JsonValue {
+    node_to_json(file_system, 0)
+}
+
+fn node_to_json(file_system: &FileSystem, idx: usize) -> JsonValue {
+    let val = file_system.get(idx);
+    match val {
+        FSObject::File(file) => object! {
+            name: val.base_name(),
+            type: "file",
+            size: file.size
+        },
+        FSObject::Dir(dir) => {
+            let children: Vec<JsonValue> = file_system.sorted_children(idx).into_iter().map(|child| node_to_json(file_system, child)).collect();
+            object! {
+                name: val.base_name(),
+                type: "dir",
+                size: dir.size,
+                children: children
+            }
+        }
+    }
+}
+
+// Directory sizes in the input are ignored and recomputed from the files
+pub fn from_json(text: &str) -> Result<FileSystem, String> {
+    let root = json::parse(text).map_err(|e| e.to_string())?;
+    if root["type"] != "dir" {
+        return Err("Root of the tree must be a directory".into());
+    }
+
+    let mut file_system = FileSystem::new();
+    file_system.node(FSObject::Dir(ElfDir::new("/".into())));
+    add_children(&mut file_system, 0, "/", &root)?;
+    compute_dir_sizes(&mut file_system);
+    Ok(file_system)
+}
+
+fn add_children(file_system: &mut FileSystem, parent: usize, parent_path: &str, dir: &JsonValue) -> Result<(), String> {
+    for child in dir["children"].members() {
+        let name = child["name"].as_str().ok_or(format!("Missing name under {}", parent_path))?;
+        if name.is_empty() || name.contains('/') {
+            return Err(format!("Bad name {:?} under {}", name, parent_path));
+        }
+        match child["type"].as_str() {
+            Some("file") => {
+                let size = child["size"].as_usize().ok_or(format!("Missing size for {}{}", parent_path, name))?;
+                file_system.add_child(parent, FSObject::File(ElfFile::new(format!("{}{}", parent_path, name), size)));
+            }
+            Some("dir") => {
+                let path = format!("{}{}/", parent_path, name);
+                let idx = file_system.add_child(parent, FSObject::Dir(ElfDir::new(path.clone())));
+                add_children(file_system, idx, &path, child)?;
+            }
+            _ => return Err(format!("Unknown type for {}{}", parent_path, name)),
+        }
+    }
+    Ok(())
+}
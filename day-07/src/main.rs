@@ -1,163 +1,98 @@
 mod arena_tree;
-use std::fs;
-use arena_tree::ArenaTree;
-use regex::Regex;
+mod file_system;
+mod shell;
+mod query;
+mod json_tree;
+mod transcript;
+mod planner;
+use std::{env, fs, path::Path};
+use file_system::FileSystem;
+use planner::{plan_cleanup, Objective};
+use query::Disk;
+use shell::Shell;
 
 fn load_data() -> String {
     fs::read_to_string("data/data.txt").expect("Should have read")
 }
 
-struct ElfFile {
-    name: String,
-    size: usize
-}
-impl PartialEq for ElfFile {
-    fn eq(&self, other: &Self) -> bool {
-        self.name == other.name
-    }
-}
-impl ElfFile {
-    fn new(name: String, size: usize) -> Self {
-        Self { name, size }
-    }
-}
-struct ElfDir {
-    name: String,
-    size: usize
-}
-impl PartialEq for ElfDir {
-    fn eq(&self, other: &Self) -> bool {
-        self.name == other.name
-    }
-}
-impl ElfDir {
-    fn new(name: String) -> Self {
-        Self { name, size: 0 }
+fn parse_data(data: &str) -> FileSystem {
+    let mut shell = Shell::new();
+    shell.run(data);
+    for line in &shell.skipped {
+        eprintln!("Ignored: {}", line);
     }
+    shell.finish()
 }
 
-#[derive(PartialEq)]
-enum FSObject {
-    File(ElfFile),
-    Dir(ElfDir)
+fn part_one(file_system: &FileSystem) -> usize {
+    file_system.dirs_where(|size| size <= 100000).iter().map(|&idx| file_system.get(idx).size()).sum()
 }
-impl FSObject {
-    fn size(&self) -> usize {
-        match self {
-            Self::File(f) => f.size,
-            Self::Dir(d) => d.size
-        }
-    }
-    fn size_mut(&mut self) -> &mut usize {
-        match self {
-            Self::File(f) => &mut f.size,
-            Self::Dir(d) => &mut d.size
-        }
-    }
-    fn name(&self) -> &String {
-        match self {
-            Self::File(f) => &f.name,
-            Self::Dir(d) => &d.name
-        }
-    }
+
+fn part_two(file_system: &FileSystem, disk: &Disk) -> usize {
+    let space_to_clear = disk.space_to_clear(file_system);
+    file_system.dirs_where(|size| size >= space_to_clear).iter().map(|&idx| file_system.get(idx).size()).min().unwrap()
 }
 
-type FileSystem = ArenaTree<FSObject>;
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let option = |name: &str| args.iter().position(|arg| arg == name).map(|pos| args.get(pos + 1).expect("Option needs a value").as_str());
+
+    // The tree can come from the puzzle transcript, a JSON dump or a real directory
+    let mut file_system = if let Some(path) = option("--from-json") {
+        json_tree::from_json(&fs::read_to_string(path).expect("Should have read")).expect("Should have parsed")
+    } else if let Some(path) = option("--from-dir") {
+        transcript::from_local_dir(Path::new(path)).expect("Should have walked")
+    } else {
+        parse_data(&load_data())
+    };
+    let disk = Disk { capacity: 70000000, required_free: 30000000 };
 
-fn parse_data(data: &String) -> FileSystem {
-    let mut lines = data.lines();
-    if lines.next().unwrap_or_default() != "$ cd /" {
-        panic!("Didn't start with root dir!");
-    }
+    let total_size = part_one(&file_system);
+    println!("Part one: {}", total_size);
 
-    let mut file_system = FileSystem::new();
-    let mut current_dir = file_system.node(FSObject::Dir(ElfDir::new("/".into())));
-    let mut current_path = "/".to_string();
+    let min_delete = part_two(&file_system, &disk);
+    println!("Part two: {}", min_delete);
 
-    let cd_up_rex = Regex::new(r"^\$ cd \.\.").unwrap();
-    let cd_down_rex = Regex::new(r"^\$ cd (.*)").unwrap();
-    let file_rex = Regex::new(r"^(\d+) (.*)").unwrap();
-    for line in lines {
-        match line {
-            "$ ls" => {},
-            _cd if cd_up_rex.is_match(line) => {
-                let nested_size = file_system.arena[current_dir].val.size();
-                current_dir = file_system.arena[current_dir].parent.unwrap();
-                *file_system.arena[current_dir].val.size_mut() += nested_size;
-                current_path = file_system.arena[current_dir].val.name().clone();
-            }
-            _cd if cd_down_rex.is_match(line) => {
-                let down_dir_name = cd_down_rex.captures(line).unwrap().get(1).unwrap().as_str();
-                current_path.push_str(&down_dir_name);
-                current_path.push('/');
-                let down_dir = file_system.node(FSObject::Dir(ElfDir::new(current_path.clone())));
-                file_system.arena[current_dir].children.push(down_dir);
-                file_system.arena[down_dir].parent = Some(current_dir);
-                current_dir = down_dir;
-            },
-            _file if file_rex.is_match(line) => {
-                let file_name = file_rex.captures(line).unwrap().get(2).unwrap().as_str();
-                let file_size = file_rex.captures(line).unwrap().get(1).unwrap().as_str().parse::<usize>().unwrap();
-                
-                let mut file_path = current_path.clone();
-                file_path.push_str(&file_name);
-                let file = file_system.node(FSObject::File(ElfFile::new(file_path, file_size)));
-                file_system.arena[current_dir].children.push(file);
-                file_system.arena[file].parent = Some(current_dir);
-                *file_system.arena[current_dir].val.size_mut() += file_size;
-            }
-            _ => {}
-        }
+    if let Some(path) = option("--to-json") {
+        fs::write(path, json::stringify_pretty(json_tree::to_json(&file_system), 2)).expect("Should have written");
     }
-
-    while current_path != "/" {
-        let nested_size = file_system.arena[current_dir].val.size();
-        current_dir = file_system.arena[current_dir].parent.unwrap();
-        *file_system.arena[current_dir].val.size_mut() += nested_size;
-        current_path = file_system.arena[current_dir].val.name().clone();
+    if let Some(path) = option("--to-transcript") {
+        fs::write(path, transcript::transcript(&file_system)).expect("Should have written");
     }
-    file_system
-}
-
-fn part_one(file_system: &FileSystem) -> usize {
-    file_system.arena.iter().filter_map(|node| {
-        match &node.val {
-            FSObject::File(_) => None,
-            FSObject::Dir(dir) => {
-                if dir.size > 100000 { None }
-                else { Some(dir.size) }
+    for arg in &args {
+        match arg.as_str() {
+            "--du" => print!("{}", file_system.du()),
+            "--tree" => print!("{}", file_system.tree()),
+            "--stats" => {
+                println!("Depth: {}", file_system.max_depth());
+                for idx in file_system.largest_files(10) {
+                    println!("{}\t{} (depth {})", file_system.get(idx).size(), file_system.path(idx), file_system.depth(idx));
+                }
             }
+            _ => {}
         }
-    }).sum()
-}
-
-fn part_two(file_system: &FileSystem) -> usize {
-    let space_avail = 70000000 - file_system.arena[0].val.size();
-    let space_to_clear = 30000000 - space_avail;
-
-    file_system.arena.iter().filter_map(|node| {
-        match &node.val {
-            FSObject::File(_) => None,
-            FSObject::Dir(dir) => {
-                if dir.size < space_to_clear { None }
-                else { Some(dir.size) }
+    }
+    // Goes last, since carrying out the plan changes the tree
+    if let Some(objective) = option("--plan") {
+        let objective = match objective {
+            "bytes" => Objective::Bytes,
+            "deletions" => Objective::Deletions,
+            _ => panic!("Plan for bytes or deletions"),
+        };
+        match plan_cleanup(&file_system, disk.space_to_clear(&file_system), objective) {
+            Some(plan) => {
+                let paths: Vec<String> = plan.nodes.iter().map(|&idx| file_system.path(idx).to_string()).collect();
+                for path in &paths {
+                    println!("rm -r {}", path);
+                    file_system.remove(path);
+                }
+                println!("Frees {} in {} deletions", plan.freed, plan.nodes.len());
+                if !plan.optimal {
+                    println!("Too big to search exactly, so this may free more than it needs to");
+                }
+                println!("Free space afterwards: {}", disk.capacity.saturating_sub(file_system.total_size()));
             }
+            None => println!("Can't free enough space"),
         }
-    }).min().unwrap() 
-}
-
-fn main() {
-    let data = load_data();
-    let file_system = parse_data(&data);
-
-    let total_size = part_one(&file_system);
-    println!("Part one: {}", total_size);
-
-    let min_delete = part_two(&file_system);
-    println!("Part two: {}", min_delete);
-    //for node in file_system.arena {
-    //    println!("Index{}, path {}, size {}, inside {:?}, children {:?}", node.idx, node.val.name(), node.val.size(), node.parent, node.children);
-    //}
+    }
 }
-
-
@@ -0,0 +1,173 @@
+use std::collections::HashSet;
+use crate::file_system::FileSystem;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+    Bytes,
+    Deletions,
+}
+
+// What to delete. No node in the plan is inside another, and the root is never deleted.
+#[derive(Debug)]
+pub struct Plan {
+    pub nodes: Vec<usize>,
+    pub freed: usize,
+    // False when the search was too big to do exactly and a quicker plan was used instead
+    pub optimal: bool,
+}
+
+// The exact fewest-bytes search costs about files * (target + largest file) / 64 word
+// operations, and a u32 per byte up to target + largest file. Beyond these it would run
+// for minutes on big transcripts, so it falls back to a greedy plan.
+const BYTES_WORK_BUDGET: usize = 1 << 30;
+const BYTES_MEMORY_BUDGET: usize = 1 << 24;
+
+pub fn plan_cleanup(file_system: &FileSystem, target: usize, objective: Objective) -> Option<Plan> {
+    if target == 0 {
+        return Some(Plan { nodes: vec![], freed: 0, optimal: true });
+    }
+    if target > file_system.total_size() {
+        return None;
+    }
+    let (nodes, optimal) = match objective {
+        Objective::Bytes => match fewest_bytes(file_system, target) {
+            Some(Ok(nodes)) => (nodes, true),
+            Some(Err(TooBig)) => (smallest_single(file_system, target).unwrap_or_else(|| fewest_deletions(file_system, target)), false),
+            None => return None,
+        },
+        Objective::Deletions => (fewest_deletions(file_system, target), true),
+    };
+    let freed = nodes.iter().map(|&idx| file_system.get(idx).size()).sum();
+    Some(Plan { nodes, freed, optimal })
+}
+
+struct TooBig;
+
+// The greedy plan: the smallest one thing that frees enough, as in part two
+fn smallest_single(file_system: &FileSystem, target: usize) -> Option<Vec<usize>> {
+    file_system.descendants(0)
+        .filter(|&idx| file_system.get(idx).size() >= target)
+        .min_by_key(|&idx| file_system.get(idx).size())
+        .map(|idx| vec![idx])
+}
+
+// Deleting a directory frees exactly what deleting all its files would, so the fewest
+// bytes is a subset sum over files. Directories whose files all go are then deleted whole.
+fn fewest_bytes(file_system: &FileSystem, target: usize) -> Option<Result<Vec<usize>, TooBig>> {
+    let files: Vec<usize> = file_system.ids()
+        .filter(|&idx| !file_system.is_dir(idx) && file_system.get(idx).size() > 0)
+        .collect();
+    let largest = files.iter().map(|&idx| file_system.get(idx).size()).max()?;
+
+    // A minimal sum can't overshoot the target by a whole file, or that file could go
+    let cap = target + largest;
+    if cap > BYTES_MEMORY_BUDGET || files.len().saturating_mul(cap / 64 + 1) > BYTES_WORK_BUDGET {
+        return Some(Err(TooBig));
+    }
+    let mut reachable = vec![0u64; cap / 64 + 1];
+    reachable[0] = 1;
+    // The file whose addition first reached each sum
+    let mut first = vec![u32::MAX; cap + 1];
+
+    for (i, &file) in files.iter().enumerate() {
+        let size = file_system.get(file).size();
+        let (word_shift, bit_shift) = (size / 64, size % 64);
+        // High to low, so every word we read is still from before this file
+        for w in (word_shift..reachable.len()).rev() {
+            let mut shifted = reachable[w - word_shift] << bit_shift;
+            if bit_shift > 0 && w > word_shift {
+                shifted |= reachable[w - word_shift - 1] >> (64 - bit_shift);
+            }
+            let mut new = shifted & !reachable[w];
+            reachable[w] |= new;
+            while new != 0 {
+                let sum = w * 64 + new.trailing_zeros() as usize;
+                if sum <= cap {
+                    first[sum] = i as u32;
+                }
+                new &= new - 1;
+            }
+        }
+    }
+
+    let mut sum = (target..=cap).find(|&sum| first[sum] != u32::MAX)?;
+    let mut chosen = HashSet::new();
+    while sum > 0 {
+        let file = files[first[sum] as usize];
+        chosen.insert(file);
+        sum -= file_system.get(file).size();
+    }
+
+    let mut nodes = vec![];
+    for &child in file_system.children(0) {
+        collapse(file_system, child, &chosen, &mut nodes);
+    }
+    Some(Ok(nodes))
+}
+
+// Returns (files below, chosen files below); pushes the highest fully chosen nodes
+fn collapse(file_system: &FileSystem, idx: usize, chosen: &HashSet<usize>, nodes: &mut Vec<usize>) -> (usize, usize) {
+    if !file_system.is_dir(idx) {
+        let picked = chosen.contains(&idx);
+        if picked {
+            nodes.push(idx);
+        }
+        return (1, picked as usize);
+    }
+
+    let mark = nodes.len();
+    let (mut files, mut picked) = (0, 0);
+    for &child in file_system.children(idx) {
+        let (child_files, child_picked) = collapse(file_system, child, chosen, nodes);
+        files += child_files;
+        picked += child_picked;
+    }
+    if files > 0 && picked == files {
+        nodes.truncate(mark);
+        nodes.push(idx);
+    }
+    (files, picked)
+}
+
+// Each top-level entry beats anything inside it, so the biggest of those give the fewest
+// deletions. Then each pick is swapped for the smallest node that still does the job.
+fn fewest_deletions(file_system: &FileSystem, target: usize) -> Vec<usize> {
+    let size = |idx: usize| file_system.get(idx).size();
+    let mut top = file_system.children(0).to_vec();
+    top.sort_by_key(|&idx| std::cmp::Reverse(size(idx)));
+
+    let mut nodes = vec![];
+    let mut freed = 0;
+    for idx in top {
+        if freed >= target {
+            break;
+        }
+        nodes.push(idx);
+        freed += size(idx);
+    }
+
+    let mut by_size: Vec<usize> = file_system.descendants(0).collect();
+    by_size.sort_by_key(|&idx| size(idx));
+    for pick in 0..nodes.len() {
+        let rest = freed - size(nodes[pick]);
+        let needed = target.saturating_sub(rest);
+        let swap = by_size.iter().copied().find(|&idx| {
+            size(idx) >= needed && nodes.iter().enumerate().all(|(other, &node)| {
+                other == pick || !overlaps(file_system, node, idx)
+            })
+        });
+        if let Some(idx) = swap {
+            if size(idx) < size(nodes[pick]) {
+                freed = rest + size(idx);
+                nodes[pick] = idx;
+            }
+        }
+    }
+    nodes
+}
+
+// Is one of them inside the other?
+fn overlaps(file_system: &FileSystem, a: usize, b: usize) -> bool {
+    let common = file_system.lowest_common_ancestor(a, b);
+    common == Some(a) || common == Some(b)
+}
@@ -0,0 +1,88 @@
+use crate::file_system::{FSObject, FileSystem};
+
+pub struct Disk {
+    pub capacity: usize,
+    pub required_free: usize,
+}
+impl Disk {
+    // How much more has to go before there's enough free space. An overfull disk has
+    // to lose its overflow as well.
+    pub fn space_to_clear(&self, file_system: &FileSystem) -> usize {
+        (file_system.total_size() + self.required_free).saturating_sub(self.capacity)
+    }
+}
+
+impl FileSystem {
+    pub fn total_size(&self) -> usize {
+        if self.contains(0) { self.get(0).size() } else { 0 }
+    }
+
+    pub fn is_dir(&self, idx: usize) -> bool {
+        matches!(self.get(idx), FSObject::Dir(_))
+    }
+
+    pub fn path(&self, idx: usize) -> &str {
+        self.get(idx).name()
+    }
+
+    // Directories whose total size satisfies `pred`
+    pub fn dirs_where<P>(&self, pred: P) -> Vec<usize>
+    where
+        P: Fn(usize) -> bool
+    {
+        self.ids().filter(|&idx| self.is_dir(idx) && pred(self.get(idx).size())).collect()
+    }
+
+    pub fn largest_files(&self, n: usize) -> Vec<usize> {
+        let mut files: Vec<usize> = self.ids().filter(|&idx| !self.is_dir(idx)).collect();
+        files.sort_by_key(|&idx| std::cmp::Reverse(self.get(idx).size()));
+        files.truncate(n);
+        files
+    }
+
+    // Depth of the deepest node in the tree; the root is at depth 0
+    pub fn max_depth(&self) -> usize {
+        self.fold(0, |_, _, children: Vec<usize>| children.into_iter().map(|depth| depth + 1).max().unwrap_or(0))
+    }
+
+    pub fn sorted_children(&self, idx: usize) -> Vec<usize> {
+        let mut children = self.children(idx).to_vec();
+        children.sort_by(|&a, &b| self.get(a).base_name().cmp(self.get(b).base_name()));
+        children
+    }
+
+    // Every directory with its size, deepest first, like `du`
+    pub fn du(&self) -> String {
+        let mut out = String::new();
+        self.du_from(0, &mut out);
+        out
+    }
+
+    fn du_from(&self, idx: usize, out: &mut String) {
+        for child in self.sorted_children(idx) {
+            if self.is_dir(child) {
+                self.du_from(child, out);
+            }
+        }
+        out.push_str(&format!("{}\t{}\n", self.get(idx).size(), self.path(idx)));
+    }
+
+    // The layout in the puzzle's own example
+    pub fn tree(&self) -> String {
+        let mut out = String::new();
+        self.tree_from(0, 0, &mut out);
+        out
+    }
+
+    fn tree_from(&self, idx: usize, indent: usize, out: &mut String) {
+        let val = self.get(idx);
+        let kind = match val {
+            FSObject::Dir(_) => "dir",
+            FSObject::File(_) => "file",
+        };
+        out.push_str(&format!("{}- {} ({}, size={})\n", "  ".repeat(indent), val.base_name(), kind, val.size()));
+        for child in self.sorted_children(idx) {
+            self.tree_from(child, indent + 1, out);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::file_system::{compute_dir_sizes, ElfDir, ElfFile, FSObject, FileSystem};
+
+// Replays a terminal transcript against an empty filesystem, the way the elves'
+// shell would have seen it.
+pub struct Shell {
+    file_system: FileSystem,
+    // Node of the current directory, so each line costs the same however deep we are
+    cwd: usize,
+    listing: bool,
+    pub skipped: Vec<String>,
+}
+impl Shell {
+    pub fn new() -> Self {
+        let mut file_system = FileSystem::new();
+        file_system.node(FSObject::Dir(ElfDir::new("/".into())));
+        Self { file_system, cwd: 0, listing: false, skipped: vec![] }
+    }
+
+    pub fn run(&mut self, transcript: &str) {
+        for line in transcript.lines() {
+            self.line(line);
+        }
+    }
+
+    pub fn line(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+
+        if let Some(command) = line.strip_prefix('$') {
+            self.listing = false;
+            let command = command.trim();
+            let (name, arg) = match command.split_once(' ') {
+                Some((name, arg)) => (name, Some(arg.trim())),
+                None => (command, None),
+            };
+            match (name, arg) {
+                ("cd", Some(target)) => self.cd(target),
+                ("cd", None) => self.cwd = 0,
+                ("ls", _) => self.listing = true,
+                _ => self.skipped.push(line.to_string()),
+            }
+            return;
+        }
+
+        // Output from anything other than `ls` means nothing to us
+        if !self.listing {
+            self.skipped.push(line.to_string());
+            return;
+        }
+        match line.split_once(' ') {
+            Some(("dir", name)) => {
+                self.subdir(name);
+            }
+            Some((size, name)) if size.parse::<usize>().is_ok() => {
+                let size = size.parse::<usize>().unwrap();
+                let path = format!("{}{}", self.file_system.path(self.cwd), name);
+                let file = self.file_system.add_child(self.cwd, FSObject::File(ElfFile::new(path, size)));
+                // A later listing wins if the size has changed
+                *self.file_system.get_mut(file).size_mut() = size;
+            }
+            _ => self.skipped.push(line.to_string()),
+        }
+    }
+
+    pub fn finish(mut self) -> FileSystem {
+        compute_dir_sizes(&mut self.file_system);
+        self.file_system
+    }
+
+    fn cd(&mut self, target: &str) {
+        if target.starts_with('/') {
+            self.cwd = 0;
+        }
+        for segment in target.split('/') {
+            match segment {
+                "" | "." => {},
+                ".." => self.cwd = self.file_system.parent(self.cwd).unwrap_or(0),
+                name => self.cwd = self.subdir(name),
+            }
+        }
+    }
+
+    // Make sure the current directory has a subdirectory `name`; returns its node
+    fn subdir(&mut self, name: &str) -> usize {
+        let path = format!("{}{}/", self.file_system.path(self.cwd), name);
+        self.file_system.add_child(self.cwd, FSObject::Dir(ElfDir::new(path)))
+    }
+}
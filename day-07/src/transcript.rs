@@ -0,0 +1,53 @@
+use std::{fs, io, path::Path};
+use crate::file_system::{compute_dir_sizes, ElfDir, ElfFile, FSObject, FileSystem};
+
+// The reverse of parsing: a terminal session that visits every directory once
+pub fn transcript(file_system: &FileSystem) -> String {
+    let mut out = String::from("$ cd /\n");
+    session_from(file_system, 0, &mut out);
+    out
+}
+
+fn session_from(file_system: &FileSystem, idx: usize, out: &mut String) {
+    let children = file_system.sorted_children(idx);
+    out.push_str("$ ls\n");
+    for &child in &children {
+        let val = file_system.get(child);
+        match val {
+            FSObject::Dir(_) => out.push_str(&format!("dir {}\n", val.base_name())),
+            FSObject::File(file) => out.push_str(&format!("{} {}\n", file.size, val.base_name())),
+        }
+    }
+    for &child in &children {
+        if file_system.is_dir(child) {
+            out.push_str(&format!("$ cd {}\n", file_system.get(child).base_name()));
+            session_from(file_system, child, out);
+            out.push_str("$ cd ..\n");
+        }
+    }
+}
+
+// Mirror a real directory. Symlinks are skipped so we can't loop.
+pub fn from_local_dir(root: &Path) -> io::Result<FileSystem> {
+    let mut file_system = FileSystem::new();
+    file_system.node(FSObject::Dir(ElfDir::new("/".into())));
+    walk(&mut file_system, 0, "/", root)?;
+    compute_dir_sizes(&mut file_system);
+    Ok(file_system)
+}
+
+fn walk(file_system: &mut FileSystem, parent: usize, parent_path: &str, dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if meta.is_dir() {
+            let path = format!("{}{}/", parent_path, name);
+            let idx = file_system.add_child(parent, FSObject::Dir(ElfDir::new(path.clone())));
+            walk(file_system, idx, &path, &entry.path())?;
+        } else if meta.is_file() {
+            file_system.add_child(parent, FSObject::File(ElfFile::new(format!("{}{}", parent_path, name), meta.len() as usize)));
+        }
+    }
+    Ok(())
+}
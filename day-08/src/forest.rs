@@ -0,0 +1,57 @@
+pub type Grid<T> = Vec<Vec<T>>;
+
+pub struct Forest {
+    pub width: usize,
+    pub height: usize,
+    pub heights: Grid<u8>,
+}
+
+impl Forest {
+    pub fn parse(data: &str) -> Self {
+        let heights: Grid<u8> = data.lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.bytes().map(|b| b - b'0').collect())
+            .collect();
+        let height = heights.len();
+        let width = heights.first().map_or(0, |row| row.len());
+        Self { width, height, heights }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    // One monotonic-stack sweep per line of trees: each stack holds the trees still
+    // in view, shortest on top, so each tree is pushed and popped at most once. Every
+    // line is walked starting from the edge `dir` points at, with the forest visited
+    // row by row. `f` gets each tree with its viewing distance and whether it can see
+    // the edge.
+    pub fn sweep<F>(&self, (dx, dy): (i32, i32), mut f: F)
+    where
+        F: FnMut(usize, usize, usize, bool)
+    {
+        // Trees on the same line share x * dy - y * dx
+        let offset = (self.width + self.height) as i32;
+        let mut stacks: Vec<Vec<(usize, u8)>> = vec![vec![]; 2 * offset as usize + 1];
+        let mut steps: Vec<usize> = vec![0; stacks.len()];
+
+        let ys: Vec<usize> = if dy > 0 { (0..self.height).rev().collect() } else { (0..self.height).collect() };
+        let xs: Vec<usize> = if dx > 0 { (0..self.width).rev().collect() } else { (0..self.width).collect() };
+        for &y in &ys {
+            for &x in &xs {
+                let line = (x as i32 * dy - y as i32 * dx + offset) as usize;
+                let (stack, step) = (&mut stacks[line], steps[line]);
+                let tree = self.heights[y][x];
+                while stack.last().is_some_and(|&(_, other)| other < tree) {
+                    stack.pop();
+                }
+                match stack.last() {
+                    Some(&(blocker, _)) => f(x, y, step - blocker, false),
+                    None => f(x, y, step, true),
+                }
+                stack.push((step, tree));
+                steps[line] += 1;
+            }
+        }
+    }
+}
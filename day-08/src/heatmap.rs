@@ -0,0 +1,60 @@
+use crate::forest::Grid;
+
+// Scale a grid to 0..=255. Scenic scores are heavily skewed, so `log` spreads the
+// low end out.
+pub fn normalise(grid: &Grid<u128>, log: bool) -> Grid<u8> {
+    let scale = |value: u128| if log { (value as f64 + 1.0).ln() } else { value as f64 };
+    let max = grid.iter().flatten().map(|&value| scale(value)).fold(0.0, f64::max);
+    grid.iter().map(|row| {
+        row.iter().map(|&value| {
+            if max == 0.0 { 0 } else { (scale(value) / max * 255.0).round() as u8 }
+        }).collect()
+    }).collect()
+}
+
+pub fn mask_levels(mask: &Grid<bool>) -> Grid<u8> {
+    mask.iter().map(|row| row.iter().map(|&set| if set { 255 } else { 0 }).collect()).collect()
+}
+
+// Black through red and yellow to white
+pub fn heat_colour(level: u8) -> (u8, u8, u8) {
+    let level = level as u32 * 3;
+    let channel = |from: u32| level.saturating_sub(from).min(255) as u8;
+    (channel(0), channel(255), channel(510))
+}
+
+fn dimensions(levels: &Grid<u8>) -> (usize, usize) {
+    (levels.first().map_or(0, |row| row.len()), levels.len())
+}
+
+// Binary greyscale
+pub fn pgm(levels: &Grid<u8>) -> Vec<u8> {
+    let (width, height) = dimensions(levels);
+    let mut out = format!("P5\n{} {}\n255\n", width, height).into_bytes();
+    out.extend(levels.iter().flatten());
+    out
+}
+
+// Binary colour, through the heat palette
+pub fn ppm(levels: &Grid<u8>) -> Vec<u8> {
+    let (width, height) = dimensions(levels);
+    let mut out = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    for &level in levels.iter().flatten() {
+        let (r, g, b) = heat_colour(level);
+        out.extend([r, g, b]);
+    }
+    out
+}
+
+// Two coloured spaces per cell, so the picture isn't squashed sideways
+pub fn ansi(levels: &Grid<u8>) -> String {
+    let mut out = String::new();
+    for row in levels {
+        for &level in row {
+            let (r, g, b) = heat_colour(level);
+            out.push_str(&format!("\x1b[48;2;{};{};{}m  ", r, g, b));
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
@@ -1,122 +1,71 @@
-use std::fs;
+mod forest;
+mod sight;
+mod heatmap;
+use std::{env, fs};
+use forest::Forest;
+use sight::{LineOfSight, Observer};
 
 fn load_data() -> String {
     fs::read_to_string("data/data.txt").expect("Should have read")
 }
 
-fn part_one(data: &String) -> usize {
-    let rows = data.lines();
-    let num_rows = rows.clone().count();
-    let num_cols = rows.clone().next().unwrap().chars().count();
-
-    let mut visibility: Vec<Vec<bool>> = vec![vec![false; num_cols]; num_rows];
+fn part_one(forest: &Forest, sight: &LineOfSight) -> usize {
+    sight.visibility(forest).seen
+}
 
-    // Left
-    for (y, row) in rows.clone().enumerate() {
-        let mut tallest = '0' as i32 - 1;
-        for (x, tree) in row.chars().enumerate() {
-            if tree as i32 <= tallest { continue; }
+fn part_two(forest: &Forest, sight: &LineOfSight) -> u128 {
+    *sight.scenic_scores(forest).iter().flatten().max().unwrap()
+}
 
-            tallest = tree as i32;
-            visibility[y][x] = true;
-        }
-    }
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let sight = if args.iter().any(|arg| arg == "--diagonals") {
+        LineOfSight::with_diagonals()
+    } else {
+        LineOfSight::cardinal()
+    };
 
-    // Right
-    for (y, row) in rows.clone().enumerate() {
-        let mut tallest = '0' as i32 - 1;
-        for (x, tree) in row.chars().rev().enumerate() {
-            if tree as i32 <= tallest { continue; }
+    let data = load_data();
+    let forest = Forest::parse(&data);
+    let count = part_one(&forest, &sight);
+    println!("Part one: {}", count);
+    let scenic = part_two(&forest, &sight);
+    println!("Part two: {}", scenic);
 
-            tallest = tree as i32;
-            visibility[y][num_cols - 1 - x] = true;
-        }
+    let option = |name: &str| args.iter().position(|arg| arg == name).and_then(|pos| args.get(pos + 1)).map(String::as_str);
+    if let Some(path) = option("--visibility-pgm") {
+        fs::write(path, heatmap::pgm(&heatmap::mask_levels(&sight.visibility(&forest).mask))).expect("Should have written");
     }
-
-    // top
-    for x in 0..num_cols {
-        let mut tallest = '0' as i32 - 1;
-        for y in 0..num_rows {
-            let tree = rows.clone().nth(y).unwrap().chars().nth(x).unwrap();
-            if tree as i32 <= tallest { continue; }
-
-            tallest = tree as i32;
-            visibility[y][x] = true;
-        }
+    if let Some(path) = option("--scenic-pgm") {
+        fs::write(path, heatmap::pgm(&heatmap::normalise(&sight.scenic_scores(&forest), true))).expect("Should have written");
     }
-
-    // Bottom
-    for x in 0..num_cols {
-        let mut tallest = '0' as i32 - 1;
-        for y in (0..num_rows).rev() {
-            let tree = rows.clone().nth(y).unwrap().chars().nth(x).unwrap();
-            if tree as i32 <= tallest { continue; }
-
-            tallest = tree as i32;
-            visibility[y][x] = true;
+    if let Some(path) = option("--scenic-ppm") {
+        fs::write(path, heatmap::ppm(&heatmap::normalise(&sight.scenic_scores(&forest), true))).expect("Should have written");
+    }
+    if args.iter().any(|arg| arg == "--ansi") {
+        print!("{}", heatmap::ansi(&heatmap::mask_levels(&sight.visibility(&forest).mask)));
+        print!("{}", heatmap::ansi(&heatmap::normalise(&sight.scenic_scores(&forest), true)));
+    }
+    if let Some(k) = option("--top") {
+        for site in sight.top_sites(&forest, k.parse().expect("Top how many?")) {
+            let distances: Vec<String> = site.distances.iter().map(|((dx, dy), distance)| format!("({},{})={}", dx, dy, distance)).collect();
+            println!("({}, {}) score {}: {}", site.x, site.y, site.score, distances.join(" "));
         }
     }
 
-    visibility.iter().map(|row| {
-        row.iter().filter(|&&t| t).count()
-    }).sum()
-}
-
-fn part_two(data: &String) -> usize {
-    let rows = data.lines();
-    let num_rows = rows.clone().count();
-    let num_cols = rows.clone().next().unwrap().chars().count();
-
-    let mut visibility: Vec<Vec<usize>> = vec![vec![0; num_cols]; num_rows];
-
-    for y in 0..num_rows {
-        for x in 0..num_cols {
-            let row = rows.clone().nth(y).unwrap();
-            let house_height = row.clone().chars().nth(x).unwrap();
-
-            // Right
-            let right = match (x+1..num_cols).position(|tx| { 
-                 row.clone().chars().nth(tx).unwrap() >= house_height 
-            }) {
-                None => num_cols - x - 1,
-                Some(tx) => tx + 1
-            };
-
-            // Left
-            let left = match (1..=x).position(|tx| { 
-                 row.clone().chars().nth(x - tx).unwrap() >= house_height 
-            }) {
-                None => x,
-                Some(tx) => tx + 1
-            };
-            
-            // Down
-            let down = match (y+1..num_rows).position(|ty| { 
-                 rows.clone().nth(ty).unwrap().chars().nth(x).unwrap() >= house_height 
-            }) {
-                None => num_rows - y - 1,
-                Some(ty) => ty + 1
-            };
-            
-            // Up
-            let up = match (1..=y).position(|ty| { 
-                 rows.clone().nth(y - ty).unwrap().chars().nth(x).unwrap() >= house_height 
-            }) {
-                None => y,
-                Some(ty) => ty + 1
-            };
-
-            visibility[y][x] = right * left * up * down;
+    // --observer x,y,height
+    if let Some(spec) = option("--observer") {
+        let spec: Vec<&str> = spec.split(',').collect();
+        assert!(spec.len() == 3, "Observer is x,y,height");
+        let observer = Observer {
+            x: spec[0].parse().expect("Observer x should be a number"),
+            y: spec[1].parse().expect("Observer y should be a number"),
+            height: spec[2].parse().expect("Observer height should be 0-255"),
+        };
+        let sighting = sight.observe(&forest, observer);
+        for row in &sighting.mask {
+            println!("{}", row.iter().map(|&seen| if seen { '#' } else { '.' }).collect::<String>());
         }
+        println!("Seen {}, blocked {}", sighting.seen, sighting.blocked);
     }
-
-    *visibility.iter().flatten().max().unwrap()
-}
-
-fn main() {
-    let data = load_data();
-    let count = part_one(&data);
-    println!("Part one: {}", count);
-    let scenic = part_two(&data);
-    println!("Part two: {}", scenic);
 }
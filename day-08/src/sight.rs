@@ -0,0 +1,141 @@
+use crate::forest::{Forest, Grid};
+
+pub const CARDINALS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+pub const DIAGONALS: [(i32, i32); 4] = [(1, -1), (1, 1), (-1, 1), (-1, -1)];
+
+// Someone standing at (x, y) with their eyes at `height`. They can stand anywhere,
+// including outside the forest.
+#[derive(Clone, Copy, Debug)]
+pub struct Observer {
+    pub x: i32,
+    pub y: i32,
+    pub height: u8,
+}
+
+// Which trees can be seen. Trees on a line of sight but hidden behind another tree
+// are blocked; trees on no line of sight at all are neither.
+pub struct Sighting {
+    pub mask: Grid<bool>,
+    pub seen: usize,
+    pub blocked: usize,
+}
+
+// A candidate treehouse, with how far it can see in each direction
+#[derive(Debug)]
+pub struct Site {
+    pub x: usize,
+    pub y: usize,
+    pub score: u128,
+    pub distances: Vec<((i32, i32), usize)>,
+}
+
+pub struct LineOfSight {
+    pub directions: Vec<(i32, i32)>,
+}
+impl LineOfSight {
+    pub fn cardinal() -> Self {
+        Self { directions: CARDINALS.to_vec() }
+    }
+
+    pub fn with_diagonals() -> Self {
+        Self { directions: CARDINALS.iter().chain(DIAGONALS.iter()).copied().collect() }
+    }
+
+    // Trees that can see out of the forest in at least one direction
+    pub fn visibility(&self, forest: &Forest) -> Sighting {
+        let mut mask = vec![vec![false; forest.width]; forest.height];
+        for &dir in &self.directions {
+            forest.sweep(dir, |x, y, _, edge| mask[y][x] |= edge);
+        }
+        let seen = mask.iter().flatten().filter(|&&visible| visible).count();
+        Sighting { mask, seen, blocked: forest.width * forest.height - seen }
+    }
+
+    // Product of the viewing distances in every direction. Eight of those overflow a
+    // usize on quite small forests, so scores are u128s and saturate beyond that.
+    pub fn scenic_scores(&self, forest: &Forest) -> Grid<u128> {
+        let mut scores = vec![vec![1u128; forest.width]; forest.height];
+        for &dir in &self.directions {
+            forest.sweep(dir, |x, y, dist, _| scores[y][x] = scores[y][x].saturating_mul(dist as u128));
+        }
+        scores
+    }
+
+    // The `k` best treehouse sites, best first; ties go to the top-left-most
+    pub fn top_sites(&self, forest: &Forest, k: usize) -> Vec<Site> {
+        let scores = self.scenic_scores(forest);
+        let mut cells: Vec<(usize, usize)> = (0..forest.height).flat_map(|y| (0..forest.width).map(move |x| (x, y))).collect();
+        let by_score = |&(x, y): &(usize, usize)| (std::cmp::Reverse(scores[y][x]), y, x);
+        if k < cells.len() {
+            cells.select_nth_unstable_by_key(k, by_score);
+            cells.truncate(k);
+        }
+        cells.sort_by_key(by_score);
+
+        cells.into_iter().map(|(x, y)| Site {
+            x,
+            y,
+            score: scores[y][x],
+            distances: self.directions.iter().map(|&dir| (dir, Self::viewing_distance(forest, x, y, dir))).collect(),
+        }).collect()
+    }
+
+    // Steps from the tree at (x, y) to the first tree at least as tall, or to the edge
+    pub fn viewing_distance(forest: &Forest, x: usize, y: usize, (dx, dy): (i32, i32)) -> usize {
+        let height = forest.heights[y][x];
+        let (mut cx, mut cy) = (x as i32 + dx, y as i32 + dy);
+        let mut distance = 0;
+        while forest.contains(cx, cy) {
+            distance += 1;
+            if forest.heights[cy as usize][cx as usize] >= height {
+                break;
+            }
+            cx += dx;
+            cy += dy;
+        }
+        distance
+    }
+
+    // Walk out from the observer along each direction. As with the treehouse, the
+    // view along a line ends at the first tree at least as tall as the observer.
+    pub fn observe(&self, forest: &Forest, observer: Observer) -> Sighting {
+        let mut mask = vec![vec![false; forest.width]; forest.height];
+        let mut blocked_mask = vec![vec![false; forest.width]; forest.height];
+        for &(dx, dy) in &self.directions {
+            let (mut x, mut y) = (observer.x + dx, observer.y + dy);
+            // Cross any open ground before reaching the trees
+            while !forest.contains(x, y) && Self::heading_in(forest, x, y, dx, dy) {
+                x += dx;
+                y += dy;
+            }
+
+            let mut view_ended = false;
+            while forest.contains(x, y) {
+                let (ux, uy) = (x as usize, y as usize);
+                if view_ended {
+                    blocked_mask[uy][ux] = true;
+                } else {
+                    mask[uy][ux] = true;
+                    view_ended = forest.heights[uy][ux] >= observer.height;
+                }
+                x += dx;
+                y += dy;
+            }
+        }
+
+        // A tree seen one way and hidden another counts as seen
+        let seen = mask.iter().flatten().filter(|&&visible| visible).count();
+        let blocked = mask.iter().flatten().zip(blocked_mask.iter().flatten())
+            .filter(|&(&visible, &hidden)| hidden && !visible)
+            .count();
+        Sighting { mask, seen, blocked }
+    }
+
+    // Could stepping along (dx, dy) from outside the forest still reach it?
+    fn heading_in(forest: &Forest, x: i32, y: i32, dx: i32, dy: i32) -> bool {
+        let toward = |pos: i32, step: i32, len: usize| {
+            (pos < 0 && step > 0) || (pos >= len as i32 && step < 0) || (pos >= 0 && pos < len as i32)
+        };
+        toward(x, dx, forest.width) && toward(y, dy, forest.height)
+    }
+}
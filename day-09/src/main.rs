@@ -1,82 +1,89 @@
-use std::fs;
-
-use itertools::Itertools;
+mod rope;
+mod motion;
+mod render;
+use std::{env, fs};
+use motion::{parse_motions, Motion};
+use render::Viewport;
+use rope::{Rope, RopeReport};
 
 fn load_data() -> String {
     fs::read_to_string("data/data.txt").expect("Should have read")
 }
 
-struct Rope {
-    head: (i32, i32),
-    knots: Vec<(i32, i32)>,
-    tail_visited: Vec<(i32, i32)>
-}
-impl Rope {
-    fn new(knot_count: usize) -> Self {
-        Self {
-            head: (0, 0),
-            knots: vec![(0,0); knot_count],
-            tail_visited: vec![(0,0)]
-        }
-    }
-
-    fn apply_move(&mut self, motion: &str) {
-        let (dir, count) = motion.split_once(" ").unwrap();
-        for _ in 0..count.parse::<usize>().unwrap() {
-            match dir {
-                "L" => self.head.0 -= 1,
-                "R" => self.head.0 += 1,
-                "U" => self.head.1 -= 1,
-                "D" => self.head.1 += 1,
-                _ => unreachable!()
-            }
-            self.pull_tail();
+fn simulate(motions: &[Motion], knot_count: usize) -> RopeReport {
+    let mut rope = Rope::new(knot_count);
+    for motion in motions {
+        for _ in 0..motion.count {
+            rope.step(motion.step);
         }
     }
-
-    fn pull_tail(&mut self) {
-        let mut pulling = self.head.clone();
-        let knot_count = self.knots.len();
-        for pulled_ix in 0..knot_count {
-            {
-		        let pulled = &mut self.knots[pulled_ix];
-	
-	            // Pulled _was_ touching Pulling, so there is at most one empty space now
-		        let dx = pulling.0 - pulled.0;
-		        let dy = pulling.1 - pulled.1;
-		        if dx.abs() < 2 && dy.abs() < 2 { return; }
-		
-		        pulled.0 += dx.signum();
-		        pulled.1 += dy.signum();
-            }
-            pulling = self.knots[pulled_ix].clone();
-        }
-        self.tail_visited.push(self.knots.last().unwrap().clone());
-    }
+    rope.report()
 }
 
-fn part_one(data: &String) -> usize {
-    let mut rope = Rope::new(1);
-    for motion in data.lines() {
-        rope.apply_move(motion);
-    }
-
-    rope.tail_visited.into_iter().unique().count()
+fn part_one(motions: &[Motion]) -> usize {
+    simulate(motions, 1).tail().visited
 }
 
-fn part_two(data: &String) -> usize {
-    let mut rope = Rope::new(9);
-    for motion in data.lines() {
-        rope.apply_move(motion);
-    }
-
-    rope.tail_visited.into_iter().unique().count()
+fn part_two(motions: &[Motion]) -> usize {
+    simulate(motions, 9).tail().visited
 }
 
 fn main() {
     let data = load_data();
-    let count = part_one(&data);
+    let motions = match parse_motions(&data) {
+        Ok(motions) => motions,
+        Err(e) => {
+            eprintln!("Bad motions: {}", e);
+            return;
+        }
+    };
+    let count = part_one(&motions);
     println!("Part one: {}", count);
-    let count = part_two(&data);
+    let count = part_two(&motions);
     println!("Part two: {}", count);
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let option = |name: &str| args.iter().position(|arg| arg == name).map(|pos| args[pos + 1].as_str());
+    let knot_count = option("--knots").map_or(9, |count| count.parse().expect("How many knots?"));
+
+    // --knots N gives the full breakdown for a rope of any length
+    if option("--knots").is_some() {
+        let report = simulate(&motions, knot_count);
+        println!("{} steps", report.steps);
+        for (knot, stats) in report.knots.iter().enumerate() {
+            let first = stats.first_moved.map_or("never".to_string(), |step| format!("step {}", step));
+            println!("Knot {}: visited {}, travelled {}, first moved {}", knot, stats.visited, stats.distance, first);
+        }
+    }
+
+    if args.iter().any(|arg| arg == "--animate") {
+        let viewport = Viewport { width: 26, height: 21 };
+        let mut rope = Rope::new(knot_count);
+        println!("== Initial State ==\n\n{}", viewport.frame(&rope.knots));
+        for motion in &motions {
+            println!("== {} {} ==\n", motion.name(), motion.count);
+            for _ in 0..motion.count {
+                rope.step(motion.step);
+                println!("{}", viewport.frame(&rope.knots));
+            }
+        }
+    }
+
+    let csv_path = option("--csv");
+    let svg_path = option("--svg");
+    if csv_path.is_some() || svg_path.is_some() {
+        let mut rope = Rope::new(knot_count).record_trajectories();
+        for motion in &motions {
+            for _ in 0..motion.count {
+                rope.step(motion.step);
+            }
+        }
+        let trajectories = rope.trajectories.unwrap();
+        if let Some(path) = csv_path {
+            fs::write(path, render::trajectory_csv(&trajectories)).expect("Should have written");
+        }
+        if let Some(path) = svg_path {
+            fs::write(path, render::trajectory_svg(&trajectories)).expect("Should have written");
+        }
+    }
 }
@@ -0,0 +1,85 @@
+use crate::rope::Pos;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Motion {
+    pub step: Pos,
+    pub count: usize,
+}
+
+const DIRECTIONS: [(&str, Pos); 8] = [
+    ("L", (-1, 0)),
+    ("R", (1, 0)),
+    ("U", (0, -1)),
+    ("D", (0, 1)),
+    ("UL", (-1, -1)),
+    ("UR", (1, -1)),
+    ("DL", (-1, 1)),
+    ("DR", (1, 1)),
+];
+
+fn direction(dir: &str) -> Option<Pos> {
+    DIRECTIONS.iter().find(|&&(name, _)| name == dir).map(|&(_, step)| step)
+}
+
+impl Motion {
+    pub fn name(&self) -> &'static str {
+        DIRECTIONS.iter().find(|&&(_, step)| step == self.step).unwrap().0
+    }
+}
+
+// Motions are one per line, as `<dir> <count>`. Blank lines and anything after a
+// `#` are ignored, and
+//   repeat <n> {
+//     ...
+//   }
+// plays the enclosed motions n times. Repeats can nest. Repeats are expanded as they
+// close, so the expanded program is capped at MAX_MOTIONS.
+pub const MAX_MOTIONS: usize = 10_000_000;
+
+pub fn parse_motions(text: &str) -> Result<Vec<Motion>, String> {
+    // Each open block: (line it opened on, repeat count, motions so far)
+    let mut blocks: Vec<(usize, usize, Vec<Motion>)> = vec![(0, 1, vec![])];
+
+    for (ix, raw) in text.lines().enumerate() {
+        let line_no = ix + 1;
+        let line = raw.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line == "}" {
+            if blocks.len() == 1 {
+                return Err(format!("line {}: '}}' without a matching repeat", line_no));
+            }
+            let (opened, times, body) = blocks.pop().unwrap();
+            let outer = &mut blocks.last_mut().unwrap().2;
+            let expanded = body.len().checked_mul(times).and_then(|len| len.checked_add(outer.len()));
+            if expanded.is_none_or(|len| len > MAX_MOTIONS) {
+                return Err(format!("line {}: repeat expands to more than {} motions", opened, MAX_MOTIONS));
+            }
+            for _ in 0..times {
+                outer.extend_from_slice(&body);
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("repeat ") {
+            let times = rest.strip_suffix('{')
+                .and_then(|count| count.trim().parse::<usize>().ok())
+                .ok_or(format!("line {}: expected 'repeat <count> {{'", line_no))?;
+            blocks.push((line_no, times, vec![]));
+            continue;
+        }
+
+        let (dir, count) = line.split_once(char::is_whitespace)
+            .ok_or(format!("line {}: expected '<direction> <count>'", line_no))?;
+        let step = direction(dir).ok_or(format!("line {}: unknown direction {:?}", line_no, dir))?;
+        let count = count.trim().parse::<usize>().map_err(|_| format!("line {}: bad count {:?}", line_no, count.trim()))?;
+        blocks.last_mut().unwrap().2.push(Motion { step, count });
+    }
+
+    if blocks.len() > 1 {
+        return Err(format!("line {}: repeat is never closed", blocks.last().unwrap().0));
+    }
+    Ok(blocks.pop().unwrap().2)
+}
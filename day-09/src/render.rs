@@ -0,0 +1,77 @@
+use crate::rope::Pos;
+
+// How knot `ix` is drawn: the head is H, a lone tail is T, otherwise knots are numbered
+fn knot_char(ix: usize, knot_count: usize) -> char {
+    match ix {
+        0 => 'H',
+        1 if knot_count == 2 => 'T',
+        1..=9 => char::from_digit(ix as u32, 10).unwrap(),
+        _ => '#',
+    }
+}
+
+// A window onto the grid, centred on the head
+pub struct Viewport {
+    pub width: i32,
+    pub height: i32,
+}
+impl Viewport {
+    // Draw the rope like the puzzle's worked example. Where knots overlap the one
+    // nearer the head wins; `s` marks the start if nothing covers it.
+    pub fn frame(&self, knots: &[Pos]) -> String {
+        let head = knots[0];
+        let left = head.0 - self.width / 2;
+        let top = head.1 - self.height / 2;
+
+        let mut out = String::new();
+        for y in top..top + self.height {
+            for x in left..left + self.width {
+                let cell = match knots.iter().position(|&knot| knot == (x, y)) {
+                    Some(ix) => knot_char(ix, knots.len()),
+                    None if (x, y) == (0, 0) => 's',
+                    None => '.',
+                };
+                out.push(cell);
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+// One row per knot per step
+pub fn trajectory_csv(trajectories: &[Vec<Pos>]) -> String {
+    let mut out = String::from("step,knot,x,y\n");
+    let steps = trajectories.first().map_or(0, |trajectory| trajectory.len());
+    for step in 0..steps {
+        for (knot, trajectory) in trajectories.iter().enumerate() {
+            let (x, y) = trajectory[step];
+            out.push_str(&format!("{},{},{},{}\n", step, knot, x, y));
+        }
+    }
+    out
+}
+
+// A polyline per knot, head drawn last so it sits on top
+pub fn trajectory_svg(trajectories: &[Vec<Pos>]) -> String {
+    let all = trajectories.iter().flatten();
+    let min_x = all.clone().map(|p| p.0).min().unwrap_or(0) - 1;
+    let min_y = all.clone().map(|p| p.1).min().unwrap_or(0) - 1;
+    let max_x = all.clone().map(|p| p.0).max().unwrap_or(0) + 1;
+    let max_y = all.map(|p| p.1).max().unwrap_or(0) + 1;
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+        min_x, min_y, max_x - min_x, max_y - min_y
+    );
+    for (knot, trajectory) in trajectories.iter().enumerate().rev() {
+        let hue = knot * 360 / trajectories.len();
+        let points: Vec<String> = trajectory.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+        out.push_str(&format!(
+            "  <polyline id=\"knot-{}\" fill=\"none\" stroke=\"hsl({},80%,45%)\" stroke-width=\"0.3\" points=\"{}\"/>\n",
+            knot, hue, points.join(" ")
+        ));
+    }
+    out.push_str("</svg>\n");
+    out
+}
@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+
+pub type Pos = (i32, i32);
+
+#[derive(Clone, Debug, Default)]
+pub struct KnotStats {
+    pub visited: HashSet<Pos>,
+    // Every move counts as one, whether it's straight or diagonal
+    pub distance: usize,
+    pub first_moved: Option<usize>,
+}
+
+// Knot 0 is the head, the last knot is the tail
+pub struct Rope {
+    pub knots: Vec<Pos>,
+    pub stats: Vec<KnotStats>,
+    pub steps: usize,
+    // Every knot's position after every step, when asked for
+    pub trajectories: Option<Vec<Vec<Pos>>>,
+}
+impl Rope {
+    pub fn new(knot_count: usize) -> Self {
+        let mut stats = vec![KnotStats::default(); knot_count + 1];
+        for knot in stats.iter_mut() {
+            knot.visited.insert((0, 0));
+        }
+        Self {
+            knots: vec![(0, 0); knot_count + 1],
+            stats,
+            steps: 0,
+            trajectories: None,
+        }
+    }
+
+    pub fn record_trajectories(mut self) -> Self {
+        self.trajectories = Some(self.knots.iter().map(|&knot| vec![knot]).collect());
+        self
+    }
+
+    // Move the head one step (diagonals allowed), then let the rest of the rope follow
+    pub fn step(&mut self, (dx, dy): Pos) {
+        self.steps += 1;
+        self.knots[0].0 += dx;
+        self.knots[0].1 += dy;
+        self.moved(0);
+        self.pull_tail();
+        if let Some(trajectories) = self.trajectories.as_mut() {
+            for (trajectory, &knot) in trajectories.iter_mut().zip(&self.knots) {
+                trajectory.push(knot);
+            }
+        }
+    }
+
+    fn moved(&mut self, knot: usize) {
+        let stats = &mut self.stats[knot];
+        stats.visited.insert(self.knots[knot]);
+        stats.distance += 1;
+        stats.first_moved.get_or_insert(self.steps);
+    }
+
+    fn pull_tail(&mut self) {
+        for pulled_ix in 1..self.knots.len() {
+            let pulling = self.knots[pulled_ix - 1];
+            let pulled = &mut self.knots[pulled_ix];
+
+            // Pulled _was_ touching Pulling, so there is at most one empty space now in
+            // each axis (two, diagonally, when the knot ahead moved diagonally)
+            let dx = pulling.0 - pulled.0;
+            let dy = pulling.1 - pulled.1;
+            if dx.abs() < 2 && dy.abs() < 2 { return; }
+
+            pulled.0 += dx.signum();
+            pulled.1 += dy.signum();
+            self.moved(pulled_ix);
+        }
+    }
+
+    pub fn report(&self) -> RopeReport {
+        RopeReport {
+            steps: self.steps,
+            knots: self.stats.iter().map(|stats| KnotReport {
+                visited: stats.visited.len(),
+                distance: stats.distance,
+                first_moved: stats.first_moved,
+            }).collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct KnotReport {
+    pub visited: usize,
+    pub distance: usize,
+    pub first_moved: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct RopeReport {
+    pub steps: usize,
+    pub knots: Vec<KnotReport>,
+}
+impl RopeReport {
+    pub fn tail(&self) -> &KnotReport {
+        self.knots.last().unwrap()
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+// Which cycles the signal strength is sampled on
+pub enum Schedule {
+    // first, first + step, first + 2 * step...
+    Every { first: usize, step: usize },
+    At(Vec<usize>),
+}
+impl Schedule {
+    pub fn includes(&self, cycle: usize) -> bool {
+        match self {
+            Self::Every { first, step } => cycle >= *first && (cycle - first).is_multiple_of(*step),
+            Self::At(cycles) => cycles.contains(&cycle),
+        }
+    }
+}
+
+pub struct Config {
+    pub width: usize,
+    pub height: usize,
+    pub sprite_width: usize,
+    pub schedule: Schedule,
+    // Paint the CRT to the terminal as it goes, pausing this long per pixel
+    pub animate: Option<Duration>,
+}
+impl Config {
+    // Whether the sprite, centred on X, covers `column`. An even-width sprite hangs
+    // one more pixel to the right of X than the left.
+    pub fn sprite_covers(&self, x: i32, column: i32) -> bool {
+        let left = x - (self.sprite_width as i32 - 1) / 2;
+        column >= left && column < left + self.sprite_width as i32
+    }
+}
+impl Default for Config {
+    // The handheld device from the puzzle, without the animation
+    fn default() -> Self {
+        Self {
+            width: 40,
+            height: 6,
+            sprite_width: 3,
+            schedule: Schedule::Every { first: 20, step: 40 },
+            animate: None,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+
+pub enum Operation {
+    Noop,
+    Addx(i32)
+}
+impl Operation {
+    pub fn cycles(&self) -> usize {
+        match self {
+            Self::Noop => 1,
+            Self::Addx(_) => 2,
+        }
+    }
+}
+
+// In the same format as the puzzle input
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Noop => write!(f, "noop"),
+            Self::Addx(val) => write!(f, "addx {}", val),
+        }
+    }
+}
+
+// Anything that wants to watch the CPU tick
+pub trait Observer {
+    // Called once per cycle (counting from 1) with X as it stands during that cycle
+    fn during(&mut self, cycle: usize, x: i32);
+}
+
+pub struct Cpu {
+    pub x: i32,
+    pub cycle: usize,
+}
+impl Cpu {
+    pub fn new() -> Self {
+        Self { x: 1, cycle: 0 }
+    }
+
+    // One cycle of `op`, after `done` of its cycles have already run. Instructions only
+    // change X once all their cycles are done; returns whether this was the last.
+    pub fn tick(&mut self, op: &Operation, done: usize, observers: &mut [&mut dyn Observer]) -> bool {
+        self.cycle += 1;
+        for observer in observers.iter_mut() {
+            observer.during(self.cycle, self.x);
+        }
+        if done + 1 < op.cycles() {
+            return false;
+        }
+        match op {
+            Operation::Noop => {},
+            Operation::Addx(val) => self.x += val,
+        }
+        true
+    }
+
+    pub fn execute(&mut self, op: &Operation, observers: &mut [&mut dyn Observer]) {
+        for done in 0..op.cycles() {
+            self.tick(op, done, observers);
+        }
+    }
+
+    pub fn run(&mut self, operations: &[Operation], observers: &mut [&mut dyn Observer]) {
+        for op in operations {
+            self.execute(op, observers);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::thread;
+use std::io::Write;
+use crate::config::Config;
+use crate::cpu::Observer;
+
+// In-memory copy of the CRT: one bool per pixel, lit or dark
+pub struct Framebuffer {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<bool>,
+}
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, pixels: vec![false; width * height] }
+    }
+
+    // Pixels are drawn in reading order, wrapping back to the top after the last row
+    pub fn set(&mut self, position: usize, lit: bool) {
+        let len = self.pixels.len();
+        self.pixels[position % len] = lit;
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.width + x]
+    }
+
+    // '#' is lit, anything else is dark. Short rows are padded out with dark pixels.
+    pub fn parse(text: &str) -> Self {
+        let rows: Vec<&str> = text.lines().filter(|line| !line.is_empty()).collect();
+        let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        let mut screen = Self::new(width, rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, pixel) in row.chars().enumerate() {
+                screen.pixels[y * width + x] = pixel == '#';
+            }
+        }
+        screen
+    }
+
+    pub fn render(&self) -> String {
+        self.pixels.chunks(self.width).map(|row| {
+            row.iter().map(|&lit| if lit { '#' } else { '.' }).collect::<String>() + "\n"
+        }).collect()
+    }
+}
+
+// Draws a pixel each cycle, lit if the sprite centred on X covers it
+pub struct Crt<'a> {
+    pub config: &'a Config,
+    pub screen: Framebuffer,
+}
+impl<'a> Crt<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self { config, screen: Framebuffer::new(config.width, config.height) }
+    }
+}
+impl Observer for Crt<'_> {
+    fn during(&mut self, cycle: usize, x: i32) {
+        let position = cycle - 1;
+        let column = (position % self.screen.width) as i32;
+        let lit = self.config.sprite_covers(x, column);
+        self.screen.set(position, lit);
+
+        if let Some(pause) = self.config.animate {
+            print!("{}", if lit { "#" } else { " " });
+            if column as usize == self.screen.width - 1 {
+                println!();
+            }
+            std::io::stdout().flush().unwrap();
+            thread::sleep(pause);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::io::{BufRead, Write};
+use crate::config::Config;
+use crate::cpu::{Cpu, Operation};
+use crate::crt::Crt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    // Before this cycle is drawn
+    Cycle(usize),
+    // As soon as X changes to this
+    X(i32),
+    // Before this instruction (counting from 0) starts
+    Instruction(usize),
+}
+impl Breakpoint {
+    // cycle=N, x=N or ip=N
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (kind, val) = spec.split_once('=').ok_or(format!("Breakpoints are cycle=N, x=N or ip=N, not {:?}", spec))?;
+        let bad = || format!("Bad breakpoint value {:?}", val);
+        match kind {
+            "cycle" => val.parse().map(Self::Cycle).map_err(|_| bad()),
+            "x" => val.parse().map(Self::X).map_err(|_| bad()),
+            "ip" => val.parse().map(Self::Instruction).map_err(|_| bad()),
+            _ => Err(format!("Unknown breakpoint {:?}", kind)),
+        }
+    }
+}
+
+// Runs a program a cycle at a time, stopping on breakpoints
+pub struct Debugger<'a> {
+    pub program: &'a [Operation],
+    pub cpu: Cpu,
+    pub crt: Crt<'a>,
+    // The current instruction, and how many of its cycles have run
+    pub ip: usize,
+    pub done: usize,
+    pub breakpoints: Vec<Breakpoint>,
+    // Gets a line for every cycle run
+    pub trace: Option<Box<dyn Write>>,
+}
+impl<'a> Debugger<'a> {
+    pub fn new(program: &'a [Operation], config: &'a Config) -> Self {
+        Self { program, cpu: Cpu::new(), crt: Crt::new(config), ip: 0, done: 0, breakpoints: vec![], trace: None }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.ip >= self.program.len()
+    }
+
+    // Returns the breakpoint we've stopped on, if any
+    pub fn step_cycle(&mut self) -> Option<Breakpoint> {
+        if self.finished() {
+            return None;
+        }
+        let op = &self.program[self.ip];
+        let x = self.cpu.x;
+        let last = self.cpu.tick(op, self.done, &mut [&mut self.crt]);
+        if let Some(trace) = &mut self.trace {
+            let position = self.cpu.cycle - 1;
+            let lit = self.crt.screen.pixels[position % self.crt.screen.pixels.len()];
+            writeln!(trace, "cycle {:>4}  ip {:>4}  {:<9} X={:<4} {}", self.cpu.cycle, self.ip, op.to_string(), x, if lit { '#' } else { '.' })
+                .expect("Should have written");
+        }
+        if last {
+            self.ip += 1;
+            self.done = 0;
+        } else {
+            self.done += 1;
+        }
+        self.breakpoints.iter().copied().find(|&breakpoint| match breakpoint {
+            Breakpoint::Cycle(cycle) => self.cpu.cycle + 1 == cycle,
+            Breakpoint::X(val) => self.cpu.x == val && x != val,
+            Breakpoint::Instruction(ip) => self.done == 0 && self.ip == ip,
+        })
+    }
+
+    // Runs the rest of the current instruction
+    pub fn step_instruction(&mut self) -> Option<Breakpoint> {
+        let ip = self.ip;
+        let mut hit = None;
+        while !self.finished() && self.ip == ip {
+            hit = self.step_cycle().or(hit);
+        }
+        hit
+    }
+
+    pub fn resume(&mut self) -> Option<Breakpoint> {
+        while !self.finished() {
+            if let Some(hit) = self.step_cycle() {
+                return Some(hit);
+            }
+        }
+        None
+    }
+
+    pub fn state(&self) -> String {
+        let screen = &self.crt.screen;
+        let next = self.cpu.cycle % screen.pixels.len();
+        let mut out = match self.program.get(self.ip) {
+            Some(op) => format!("cycle {}, X={}, ip {}: {} ({}/{} cycles done)\n", self.cpu.cycle, self.cpu.x, self.ip, op, self.done, op.cycles()),
+            None => format!("cycle {}, X={}, finished\n", self.cpu.cycle, self.cpu.x),
+        };
+        out.push_str(&format!("beam at ({}, {})\n", next % screen.width, next / screen.width));
+        out.push_str(&screen.render());
+        out
+    }
+
+    // s[tep] [N] cycles, n[ext] [N] instructions, c[ontinue], b[reak] SPEC, d[elete], p[rint], q[uit]
+    pub fn repl(&mut self, input: impl BufRead) {
+        println!("{}", self.state());
+        for line in input.lines() {
+            let line = line.expect("Should have read");
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let count = || words.get(1).and_then(|n| n.parse::<usize>().ok()).unwrap_or(1);
+            let hit = match words.first().copied() {
+                Some("s" | "step") => (0..count()).find_map(|_| self.step_cycle()),
+                Some("n" | "next") => (0..count()).find_map(|_| self.step_instruction()),
+                Some("c" | "continue") => self.resume(),
+                Some("b" | "break") => {
+                    match words.get(1).map(|spec| Breakpoint::parse(spec)) {
+                        Some(Ok(breakpoint)) => self.breakpoints.push(breakpoint),
+                        Some(Err(e)) => println!("{}", e),
+                        None => println!("{:?}", self.breakpoints),
+                    }
+                    continue;
+                }
+                Some("d" | "delete") => {
+                    self.breakpoints.clear();
+                    continue;
+                }
+                Some("p" | "print") => None,
+                Some("q" | "quit") => break,
+                Some(other) => {
+                    println!("Unknown command {:?}", other);
+                    continue;
+                }
+                None => continue,
+            };
+            if let Some(breakpoint) = hit {
+                println!("Stopped at {:?}", breakpoint);
+            }
+            println!("{}", self.state());
+        }
+    }
+}
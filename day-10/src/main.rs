@@ -1,79 +1,144 @@
-use std::fs;
-use std::{thread, time};
-use std::io::{stdout, Write};
-// use itertools::Itertools;
+mod config;
+mod cpu;
+mod debugger;
+mod crt;
+mod ocr;
+mod signal;
+mod synth;
+use std::{env, fs, io, process, time::Duration};
+use config::{Config, Schedule};
+use cpu::{Cpu, Operation};
+use crt::{Crt, Framebuffer};
+use debugger::{Breakpoint, Debugger};
+use signal::SignalSampler;
+use synth::Goal;
 
 fn load_data() -> String {
     fs::read_to_string("data/data.txt").expect("Should have read")
 }
 
-enum Operation {
-    Noop,
-    Addx(i32)
-}
-
-fn parse_data(data: String) -> Vec<Operation> {
-    data.lines().map(|line| {
-        match &line[0..4] {
-            "noop" => Operation::Noop,
-            "addx" => Operation::Addx(line[5..].parse::<i32>().unwrap()),
-            _ => unreachable!()
+fn parse_data(data: String) -> Result<Vec<Operation>, String> {
+    data.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).map(|(ix, line)| {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("noop"), None) => Ok(Operation::Noop),
+            (Some("addx"), Some(val)) => val.parse::<i32>()
+                .map(Operation::Addx)
+                .map_err(|_| format!("line {}: bad addx value {:?}", ix + 1, val)),
+            _ => Err(format!("line {}: unknown instruction {:?}", ix + 1, line)),
         }
     }).collect()
 }
 
-fn parts_one_and_two(operations: &Vec<Operation>) -> i32 {
-    let mut register_x: i32 = 1;
-    let mut strength: i32 = 0;
-    let mut cycle: i32 = 0;
-    let pause = time::Duration::from_millis(10);
+// Headless unless the config asks for animation: nothing here touches the terminal
+fn parts_one_and_two(operations: &[Operation], config: &Config) -> (i32, Framebuffer) {
+    let mut sampler = SignalSampler::new(&config.schedule);
+    let mut crt = Crt::new(config);
+    Cpu::new().run(operations, &mut [&mut sampler, &mut crt]);
+    (sampler.strength, crt.screen)
+}
 
-    for op in operations {
-        let mut crd = false;
-        if (register_x - cycle % 40).abs() <= 1 {
-            print!("#");
-        } else {
-            print!(" ");
-        }
-        std::io::stdout().flush().unwrap();
-        thread::sleep(pause);
-        cycle += 1;
-        if (cycle % 40) == 20 {
-            strength += cycle * register_x;
-        }
-        if cycle % 40 == 0 {
-            println!("");
-            crd = true;
-        }
-        match op {
-            Operation::Noop => {},
-            Operation::Addx(val) => {
-                if (register_x - cycle % 40).abs() <= 1 {
-                    print!("#");
-                } else {
-                    print!(" ");
-                }
-                std::io::stdout().flush().unwrap();
-                thread::sleep(pause);
-                cycle += 1;
-                if (cycle % 40) == 20 {
-                    strength += cycle * register_x;
-                }
-                register_x += val;
-            }
-        }
-        if !crd && cycle % 40 == 0 {
-            println!("");
+fn config_from_args() -> Config {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let option = |name: &str| args.iter().position(|arg| arg == name).and_then(|pos| args.get(pos + 1));
+    let number = |name: &str| option(name).map(|n| n.parse::<usize>().expect("Options take numbers"));
+
+    let mut config = Config::default();
+    if let Some(width) = number("--width") { config.width = width; }
+    if let Some(height) = number("--height") { config.height = height; }
+    if let Some(sprite_width) = number("--sprite") { config.sprite_width = sprite_width; }
+    // --sample 20,40 is every 40 cycles from 20; --sample 5,17,101 is just those cycles
+    if let Some(spec) = option("--sample") {
+        let cycles: Vec<usize> = spec.split(',').map(|n| n.parse().expect("Sample cycles are numbers")).collect();
+        config.schedule = match cycles[..] {
+            [first, step] => Schedule::Every { first, step },
+            _ => Schedule::At(cycles),
+        };
+    }
+    if args.iter().any(|arg| arg == "--animate") {
+        let pause = number("--pause").unwrap_or(10);
+        config.animate = Some(Duration::from_millis(pause as u64));
+    }
+    config
+}
+
+// --synth-text TEXT or --synth-bitmap FILE prints a program that draws it, and checks it does
+fn synthesise_from_args(config: &Config) -> Option<Result<String, String>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let option = |name: &str| args.iter().position(|arg| arg == name).and_then(|pos| args.get(pos + 1));
+    let target = if let Some(text) = option("--synth-text") {
+        match ocr::render_text(text, config.width, config.height) {
+            Ok(target) => target,
+            Err(e) => return Some(Err(e)),
         }
+    } else if let Some(path) = option("--synth-bitmap") {
+        Framebuffer::parse(&fs::read_to_string(path).expect("Should have read"))
+    } else {
+        return None;
+    };
+    let goal = if args.iter().any(|arg| arg == "--minimise") { Goal::FewestInstructions } else { Goal::FewestChanges };
+
+    let config = Config { width: target.width, height: target.height, sprite_width: config.sprite_width, schedule: Schedule::At(vec![]), animate: None };
+    let program = match synth::synthesise(&target, &config, goal) {
+        Ok(program) => program,
+        Err(e) => return Some(Err(e)),
+    };
+    let text = synth::program_text(&program);
+    let replayed = parse_data(text.clone()).expect("Should have parsed");
+    assert_eq!(parts_one_and_two(&replayed, &config).1.pixels, target.pixels, "Program should draw the target");
+    Some(Ok(text))
+}
+
+// --debug steps through interactively, stopping on any --break cycle=N,x=N,ip=N.
+// --trace FILE writes a line per cycle, running straight through without --debug.
+fn debug_from_args(operations: &[Operation], config: &Config) -> bool {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let option = |name: &str| args.iter().position(|arg| arg == name).and_then(|pos| args.get(pos + 1));
+    let interactive = args.iter().any(|arg| arg == "--debug");
+    let trace = option("--trace");
+    if !interactive && trace.is_none() {
+        return false;
     }
 
-    strength
+    let mut debugger = Debugger::new(operations, config);
+    if let Some(specs) = option("--break") {
+        debugger.breakpoints = specs.split(',').map(|spec| Breakpoint::parse(spec).expect("Should have parsed")).collect();
+    }
+    if let Some(path) = trace {
+        debugger.trace = Some(Box::new(io::BufWriter::new(fs::File::create(path).expect("Should have created"))));
+    }
+    if interactive {
+        debugger.repl(io::stdin().lock());
+    } else {
+        while !debugger.finished() {
+            debugger.resume();
+        }
+    }
+    true
 }
 
 fn main() {
+    let config = config_from_args();
+    match synthesise_from_args(&config) {
+        Some(Ok(program)) => {
+            print!("{}", program);
+            return;
+        }
+        Some(Err(e)) => {
+            eprintln!("Can't synthesise: {}", e);
+            process::exit(1);
+        }
+        None => {}
+    }
     let data = load_data();
-    let operations = parse_data(data);
-    let strength = parts_one_and_two(&operations);
+    let operations = parse_data(data).expect("Should have parsed");
+    if debug_from_args(&operations, &config) {
+        return;
+    }
+    let (strength, screen) = parts_one_and_two(&operations, &config);
     println!("Part one: {}", strength);
+    match ocr::decode(&screen) {
+        Ok(letters) => println!("Part two: {}", letters),
+        Err(e) => println!("Part two: {}\n{}", e, screen.render()),
+    }
 }
-
@@ -0,0 +1,90 @@
+use std::fmt;
+use crate::crt::Framebuffer;
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+// Each letter is followed by a blank column
+pub const GLYPH_PITCH: usize = 5;
+
+// The letters Advent of Code draws, row by row
+const GLYPHS: [(char, [&str; GLYPH_HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+pub fn glyph(letter: char) -> Option<&'static [&'static str; GLYPH_HEIGHT]> {
+    GLYPHS.iter().find(|(c, _)| *c == letter).map(|(_, rows)| rows)
+}
+
+// The reverse of `decode`: draw text in the letters the device uses
+pub fn render_text(text: &str, width: usize, height: usize) -> Result<Framebuffer, String> {
+    let mut screen = Framebuffer::new(width, height);
+    for (ix, letter) in text.chars().enumerate() {
+        let left = ix * GLYPH_PITCH;
+        if left + GLYPH_WIDTH > width || height < GLYPH_HEIGHT {
+            return Err(format!("{:?} doesn't fit on a {}x{} screen", text, width, height));
+        }
+        if letter == ' ' {
+            continue;
+        }
+        let rows = glyph(letter).ok_or(format!("No glyph for {:?}", letter))?;
+        for (y, row) in rows.iter().enumerate() {
+            for (x, pixel) in row.chars().enumerate() {
+                screen.pixels[y * width + left + x] = pixel == '#';
+            }
+        }
+    }
+    Ok(screen)
+}
+
+#[derive(Debug)]
+pub struct OcrError {
+    // What could be read, with '?' for each unknown glyph
+    pub partial: String,
+    // Left-hand pixel column of each unknown glyph
+    pub columns: Vec<usize>,
+}
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let columns: Vec<String> = self.columns.iter().map(|c| c.to_string()).collect();
+        write!(f, "unknown glyph at column {} (read {:?})", columns.join(", "), self.partial)
+    }
+}
+
+pub fn decode(screen: &Framebuffer) -> Result<String, OcrError> {
+    let mut letters = String::new();
+    let mut columns = vec![];
+    // Too short a screen can't hold any letters
+    let width = if screen.height < GLYPH_HEIGHT { 0 } else { screen.width };
+    for left in (0..width).step_by(GLYPH_PITCH).take_while(|left| left + GLYPH_WIDTH <= width) {
+        let found = GLYPHS.iter().find(|(_, rows)| {
+            rows.iter().enumerate().all(|(y, row)| {
+                row.chars().enumerate().all(|(x, pixel)| screen.get(left + x, y) == (pixel == '#'))
+            })
+        });
+        match found {
+            Some((letter, _)) => letters.push(*letter),
+            None => {
+                letters.push('?');
+                columns.push(left);
+            }
+        }
+    }
+
+    if columns.is_empty() { Ok(letters) } else { Err(OcrError { partial: letters, columns }) }
+}
@@ -0,0 +1,20 @@
+use crate::config::Schedule;
+use crate::cpu::Observer;
+
+// Adds up cycle * X on the scheduled cycles
+pub struct SignalSampler<'a> {
+    pub schedule: &'a Schedule,
+    pub strength: i32,
+}
+impl<'a> SignalSampler<'a> {
+    pub fn new(schedule: &'a Schedule) -> Self {
+        Self { schedule, strength: 0 }
+    }
+}
+impl Observer for SignalSampler<'_> {
+    fn during(&mut self, cycle: usize, x: i32) {
+        if self.schedule.includes(cycle) {
+            self.strength += cycle as i32 * x;
+        }
+    }
+}
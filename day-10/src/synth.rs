@@ -0,0 +1,84 @@
+use crate::config::Config;
+use crate::cpu::Operation;
+use crate::crt::Framebuffer;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    // As few addx as possible, filling in with noops
+    FewestChanges,
+    // As short a program as possible
+    FewestInstructions,
+}
+
+// Find a program that makes the CRT draw exactly `target`, one cycle per pixel.
+//
+// X only changes at the end of an addx, which holds X for both its cycles, so this
+// is a shortest path over (cycle, X) at instruction boundaries. X only needs to range
+// far enough either side of the screen to light nothing at all. X starts at 1 and
+// can't move before cycle 3, so not every image can be drawn.
+pub fn synthesise(target: &Framebuffer, config: &Config, goal: Goal) -> Result<Vec<Operation>, String> {
+    let cycles = target.width * target.height;
+    let reach = config.sprite_width as i32 + 1;
+    let xs: Vec<i32> = (-reach..target.width as i32 + reach).collect();
+    let x_ix = |x: i32| (x + reach) as usize;
+    let allowed = |cycle: usize, x: i32| config.sprite_covers(x, (cycle % target.width) as i32) == target.pixels[cycle];
+
+    // best[cycle][x] = (cost, previous boundary, previous X, operation that got here)
+    type Step = ((usize, usize), usize, i32, Option<i32>);
+    let mut best: Vec<Vec<Option<Step>>> = vec![vec![None; xs.len()]; cycles + 1];
+    best[0][x_ix(1)] = Some(((0, 0), 0, 1, None));
+
+    // Costs are kept as (changes, instructions) and ranked by the goal
+    let ranked = |(changes, instructions): (usize, usize)| match goal {
+        Goal::FewestChanges => (changes, instructions),
+        Goal::FewestInstructions => (instructions, changes),
+    };
+    let relax = |best: &mut Vec<Vec<Option<Step>>>, to: usize, x: i32, step: Step| {
+        let slot = &mut best[to][x_ix(x)];
+        if slot.is_none_or(|current| ranked(step.0) < ranked(current.0)) {
+            *slot = Some(step);
+        }
+    };
+
+    for cycle in 0..cycles {
+        for &x in &xs {
+            let Some((so_far, _, _, _)) = best[cycle][x_ix(x)] else { continue };
+            if !allowed(cycle, x) {
+                continue;
+            }
+            let (changes, instructions) = so_far;
+            relax(&mut best, cycle + 1, x, ((changes, instructions + 1), cycle, x, None));
+            if cycle + 2 <= cycles && allowed(cycle + 1, x) {
+                for &next in &xs {
+                    relax(&mut best, cycle + 2, next, ((changes + 1, instructions + 1), cycle, x, Some(next - x)));
+                }
+            }
+        }
+    }
+
+    let end = xs.iter().filter_map(|&x| best[cycles][x_ix(x)].map(|step| (x, step)))
+        .min_by_key(|(_, step)| ranked(step.0));
+    let Some((mut x, _)) = end else {
+        let stuck = (0..cycles).rev().find(|&cycle| best[cycle].iter().any(|step| step.is_some())).unwrap_or(0);
+        return Err(format!("Can't draw that image: no program gets pixel {} right", stuck));
+    };
+
+    let mut program = vec![];
+    let mut cycle = cycles;
+    while cycle > 0 {
+        let (_, from, from_x, op) = best[cycle][x_ix(x)].unwrap();
+        program.push(match op {
+            None => Operation::Noop,
+            Some(val) => Operation::Addx(val),
+        });
+        cycle = from;
+        x = from_x;
+    }
+    program.reverse();
+    Ok(program)
+}
+
+// In the same format as the puzzle input
+pub fn program_text(program: &[Operation]) -> String {
+    program.iter().map(|op| format!("{}\n", op)).collect()
+}
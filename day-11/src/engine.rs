@@ -0,0 +1,36 @@
+use std::cmp::Reverse;
+use num::BigUint;
+use crate::monkey::{divisor_lcm, play_round, Monkey, Observer, Relief};
+
+// How long the monkeys play for, and how worry levels come down between inspections
+pub struct Engine {
+    pub rounds: usize,
+    pub relief: Relief,
+}
+impl Engine {
+    pub fn part_one() -> Self {
+        Self { rounds: 20, relief: Relief::DivideBy(3) }
+    }
+
+    pub fn part_two(monkeys: &[Monkey]) -> Self {
+        Self { rounds: 10000, relief: Relief::Modulo(divisor_lcm(monkeys)) }
+    }
+
+    // Plays on a copy of the troop. Returns how many items each monkey inspected.
+    pub fn run(&self, monkeys: &[Monkey], observers: &mut [&mut dyn Observer]) -> Result<Vec<u64>, String> {
+        self.relief.suits(monkeys)?;
+        let mut troop = monkeys.to_vec();
+        for _ in 0..self.rounds {
+            play_round(&mut troop, &self.relief, observers)?;
+        }
+        Ok(troop.iter().map(|monkey| monkey.inspected_count).collect())
+    }
+}
+
+// The product of the `top` highest inspection counts. Enough counts overflow any fixed
+// width, so it's a big integer.
+pub fn monkey_business(inspections: &[u64], top: usize) -> BigUint {
+    let mut counts = inspections.to_vec();
+    counts.sort_unstable_by_key(|&count| Reverse(count));
+    counts.iter().take(top).map(|&count| BigUint::from(count)).product()
+}
@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+use num::{BigUint, Zero};
+use crate::monkey::{divisor_lcm, play_round, Monkey, Relief};
+
+// Worry levels tracked exactly, however big they get
+pub struct ExactTroop<'a> {
+    pub monkeys: &'a [Monkey],
+    pub items: Vec<VecDeque<BigUint>>,
+    pub counts: Vec<u64>,
+    pub relief: Relief,
+}
+impl<'a> ExactTroop<'a> {
+    pub fn new(monkeys: &'a [Monkey], relief: Relief) -> Self {
+        let items = monkeys.iter().map(|monkey| monkey.items.iter().map(|&item| BigUint::from(item)).collect()).collect();
+        Self { monkeys, items, counts: vec![0; monkeys.len()], relief }
+    }
+
+    pub fn play_round(&mut self) -> Result<(), String> {
+        for (ix, monkey) in self.monkeys.iter().enumerate() {
+            while let Some(item) = self.items[ix].pop_front() {
+                self.counts[ix] += 1;
+                let mut worry = monkey.operation.eval(&item).map_err(|e| format!("Monkey {}: {}", monkey.id, e))?;
+                match &self.relief {
+                    Relief::DivideBy(k) => worry /= *k,
+                    Relief::Modulo(m) => worry %= *m,
+                    Relief::None => {},
+                    // Custom relief is written for u64s, so it can only run while worry fits in one
+                    Relief::Custom(f) => {
+                        let small = u64::try_from(&worry)
+                            .map_err(|_| format!("Monkey {}: worry level {} is too big for custom relief", monkey.id, worry))?;
+                        worry = BigUint::from(f(small));
+                    }
+                }
+                let next_monkey = if (&worry % monkey.divisor).is_zero() { monkey.if_true } else { monkey.if_false };
+                self.items[next_monkey].push_back(worry);
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn exact_inspections(monkeys: &[Monkey], rounds: usize, relief: Relief) -> Result<Vec<u64>, String> {
+    relief.suits(monkeys)?;
+    let mut troop = ExactTroop::new(monkeys, relief);
+    for _ in 0..rounds {
+        troop.play_round()?;
+    }
+    Ok(troop.counts)
+}
+
+// Plays the u64 paths alongside exact arithmetic, round by round: dividing by 3 should
+// match exactly, and working modulo the LCM of the divisors should match no relief at all,
+// modulo the LCM. The u64 paths use checked arithmetic, so an overflow is an error too.
+pub fn check_shortcuts(monkeys: &[Monkey], rounds: usize) -> Result<(), String> {
+    let modulus = divisor_lcm(monkeys);
+    Relief::Modulo(modulus).suits(monkeys)?;
+    for part_one in [true, false] {
+        let mode = if part_one { "dividing by 3" } else { "modulo the LCM" };
+        let mut fast = monkeys.to_vec();
+        let mut exact = ExactTroop::new(monkeys, if part_one { Relief::DivideBy(3) } else { Relief::None });
+        for round in 1..=rounds {
+            play_round(&mut fast, &if part_one { Relief::DivideBy(3) } else { Relief::Modulo(modulus) }, &mut [])?;
+            exact.play_round()?;
+            let counts: Vec<u64> = fast.iter().map(|monkey| monkey.inspected_count).collect();
+            if counts != exact.counts {
+                return Err(format!("Inspection counts differ after round {} {}: {:?} vs exact {:?}", round, mode, counts, exact.counts));
+            }
+            for (monkey, items) in fast.iter().zip(&exact.items) {
+                let expected: Vec<BigUint> = items.iter().map(|worry| if part_one { worry.clone() } else { worry % modulus }).collect();
+                let got: Vec<BigUint> = monkey.items.iter().map(|&item| BigUint::from(item)).collect();
+                if got != expected {
+                    return Err(format!("Monkey {} holds different worry levels after round {} {}", monkey.id, round, mode));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::monkey::{parse_troop, Monkey};
+    use super::check_shortcuts;
+
+    fn troop(path: &str) -> Vec<Monkey> {
+        parse_troop(&fs::read_to_string(path).expect("Should have read")).expect("Should have parsed")
+    }
+
+    #[test]
+    fn shortcuts_match_exact_on_sample() {
+        assert_eq!(check_shortcuts(&troop("data/data-sample.txt"), 20), Ok(()));
+    }
+
+    #[test]
+    fn shortcuts_match_exact_on_real_data() {
+        assert_eq!(check_shortcuts(&troop("data/data-real.txt"), 20), Ok(()));
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        let mut monkeys = troop("data/data-sample.txt");
+        monkeys[0].operation = crate::expr::Expr::parse("old * old * old * old * old").unwrap();
+        let error = check_shortcuts(&monkeys, 20).unwrap_err();
+        assert!(error.contains("overflows"), "{}", error);
+    }
+}
@@ -0,0 +1,196 @@
+use std::fmt;
+use num::traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+impl BinOp {
+    fn symbol(&self) -> char {
+        match self {
+            Self::Add => '+',
+            Self::Sub => '-',
+            Self::Mul => '*',
+            Self::Div => '/',
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Add | Self::Sub => 1,
+            Self::Mul | Self::Div => 2,
+        }
+    }
+}
+
+// The right hand side of "new = ..."
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Old,
+    Const(u64),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+impl Expr {
+    // Works for any unsigned integer type, so the same expression can run on u64 or big
+    // integers. Overflow, going below zero and dividing by zero are errors.
+    pub fn eval<T>(&self, old: &T) -> Result<T, String>
+    where
+        T: Clone + From<u64> + CheckedAdd + CheckedSub + CheckedMul + CheckedDiv
+    {
+        match self {
+            Self::Old => Ok(old.clone()),
+            Self::Const(val) => Ok(T::from(*val)),
+            Self::Binary(op, left, right) => {
+                let (left, right) = (left.eval(old)?, right.eval(old)?);
+                let result = match op {
+                    BinOp::Add => left.checked_add(&right),
+                    BinOp::Sub => left.checked_sub(&right),
+                    BinOp::Mul => left.checked_mul(&right),
+                    BinOp::Div => left.checked_div(&right),
+                };
+                result.ok_or_else(|| match op {
+                    BinOp::Sub => format!("{} goes below zero", self),
+                    BinOp::Div => format!("{} divides by zero", self),
+                    _ => format!("{} overflows", self),
+                })
+            }
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenise(text)?;
+        let mut pos = 0;
+        let expr = parse_sum(&tokens, &mut pos)?;
+        match tokens.get(pos) {
+            None => Ok(expr),
+            Some(token) => Err(format!("Unexpected {:?} in {:?}", token, text)),
+        }
+    }
+
+    // Only + and *, whose results modulo m only depend on their operands modulo m
+    pub fn is_polynomial(&self) -> bool {
+        match self {
+            Self::Old | Self::Const(_) => true,
+            Self::Binary(op, left, right) => {
+                matches!(op, BinOp::Add | BinOp::Mul) && left.is_polynomial() && right.is_polynomial()
+            }
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Binary(op, _, _) => op.precedence(),
+            _ => u8::MAX,
+        }
+    }
+}
+
+// Only brackets what needs it: a right operand of equal precedence does, as - and / aren't associative
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Old => write!(f, "old"),
+            Self::Const(val) => write!(f, "{}", val),
+            Self::Binary(op, left, right) => {
+                if left.precedence() < op.precedence() {
+                    write!(f, "({})", left)?;
+                } else {
+                    write!(f, "{}", left)?;
+                }
+                write!(f, " {} ", op.symbol())?;
+                if right.precedence() <= op.precedence() {
+                    write!(f, "({})", right)
+                } else {
+                    write!(f, "{}", right)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Old,
+    Num(u64),
+    Op(BinOp),
+    Open,
+    Close,
+}
+
+fn tokenise(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' => { chars.next(); },
+            '(' => { chars.next(); tokens.push(Token::Open); },
+            ')' => { chars.next(); tokens.push(Token::Close); },
+            '+' => { chars.next(); tokens.push(Token::Op(BinOp::Add)); },
+            '-' => { chars.next(); tokens.push(Token::Op(BinOp::Sub)); },
+            '*' => { chars.next(); tokens.push(Token::Op(BinOp::Mul)); },
+            '/' => { chars.next(); tokens.push(Token::Op(BinOp::Div)); },
+            '0'..='9' => {
+                let mut digits = String::new();
+                while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                    digits.push(d);
+                    chars.next();
+                }
+                tokens.push(Token::Num(digits.parse().map_err(|_| format!("Number too big: {}", digits))?));
+            }
+            _ => {
+                let word: String = std::iter::from_fn(|| chars.next_if(|c| c.is_alphanumeric())).collect();
+                match word.as_str() {
+                    "old" => tokens.push(Token::Old),
+                    _ => return Err(format!("Unexpected {:?} in {:?}", if word.is_empty() { c.to_string() } else { word }, text)),
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// sum := product (('+' | '-') product)*
+fn parse_sum(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
+    let mut expr = parse_product(tokens, pos)?;
+    while let Some(Token::Op(op @ (BinOp::Add | BinOp::Sub))) = tokens.get(*pos) {
+        *pos += 1;
+        expr = Expr::Binary(*op, Box::new(expr), Box::new(parse_product(tokens, pos)?));
+    }
+    Ok(expr)
+}
+
+// product := atom (('*' | '/') atom)*
+fn parse_product(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
+    let mut expr = parse_atom(tokens, pos)?;
+    while let Some(Token::Op(op @ (BinOp::Mul | BinOp::Div))) = tokens.get(*pos) {
+        *pos += 1;
+        let right = parse_atom(tokens, pos)?;
+        if *op == BinOp::Div && right == Expr::Const(0) {
+            return Err("Division by 0".into());
+        }
+        expr = Expr::Binary(*op, Box::new(expr), Box::new(right));
+    }
+    Ok(expr)
+}
+
+// atom := 'old' | number | '(' sum ')'
+fn parse_atom(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
+    *pos += 1;
+    match tokens.get(*pos - 1) {
+        Some(Token::Old) => Ok(Expr::Old),
+        Some(Token::Num(val)) => Ok(Expr::Const(*val)),
+        Some(Token::Open) => {
+            let expr = parse_sum(tokens, pos)?;
+            if tokens.get(*pos) != Some(&Token::Close) {
+                return Err("Missing )".into());
+            }
+            *pos += 1;
+            Ok(expr)
+        }
+        Some(token) => Err(format!("Unexpected {:?}", token)),
+        None => Err("Expression ends too soon".into()),
+    }
+}
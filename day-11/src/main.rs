@@ -1,127 +1,107 @@
-use std::{fs, fmt};
-use std::collections::VecDeque;
-use itertools::Itertools;
-use regex::Regex;
-use num::integer::{gcd, lcm};
+mod engine;
+mod exact;
+mod expr;
+mod monkey;
+mod skip;
+mod trace;
+use std::{env, fs, rc::Rc};
+use num::BigUint;
+use engine::{monkey_business, Engine};
+use monkey::{divisor_lcm, parse_troop, Monkey, Relief};
+use trace::Tracer;
 
 fn load_data() -> String {
     fs::read_to_string("data/data.txt").expect("Should have read")
 }
 
-struct Monkey {
-    id: usize,
-    items: VecDeque<u64>,
-    operation: Box<dyn Fn(u64, bool, u64) -> u64>,
-    action: Box<dyn Fn(u64) -> usize>,
-    inspected_count: usize,
-    modulus: u64
+fn part_one(monkeys: &[Monkey]) -> BigUint {
+    monkey_business(&Engine::part_one().run(monkeys, &mut []).expect("Should have played"), 2)
 }
-impl fmt::Debug for Monkey {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Monkey")
-         .field("Modulus", &self.modulus)
-         .finish()
-    }
-}
-impl Monkey {
-    fn from_input(input: &str, id: usize) -> Self {
-        let mut lines = input.lines().skip(1);
-        
-        let items_rex = Regex::new(r"^\s*Starting items: ([\d, ]*)$").unwrap();
-        let item_parts = items_rex.captures(lines.next().unwrap()).unwrap().get(1).unwrap().as_str().split(", ");
-        let items = item_parts.map(|item| item.parse::<u64>().unwrap()).collect::<VecDeque<u64>>();
-
-        let op_rex = Regex::new(r"\s*Operation: new = old ([+*]) (\d+|old)$").unwrap();
-        let caps = op_rex.captures(lines.next().unwrap()).unwrap();
-        let oper = caps.get(1).unwrap().as_str().to_string();
-        let opand = caps.get(2).unwrap().as_str().to_string();
-        let operation = Box::new(move |old: u64, part_one: bool, modulus: u64| -> u64 {
-            let opand_val = match opand.as_str() {
-               "old" => old,
-               digits => digits.parse::<u64>().unwrap(),
-            };
-
-            let pre_bored = match oper.as_str() {
-                "+" => old + opand_val,
-                "*" => old * opand_val,
-                _ => unreachable!()
-            };
 
-            if part_one {
-                pre_bored / 3
-            } else {
-                pre_bored % modulus
-            }
-        });
-
-        let test_rex = Regex::new(r"\s*Test: divisible by (\d*)$").unwrap();
-        let divisible = test_rex.captures(lines.next().unwrap()).unwrap().get(1).unwrap().as_str().parse::<u64>().unwrap();
-        let true_monkey = lines.next().unwrap().chars().last().unwrap().to_digit(10).unwrap() as usize;
-        let false_monkey = lines.next().unwrap().chars().last().unwrap().to_digit(10).unwrap() as usize;
-        let action = Box::new(move |worry: u64| -> usize {
-            if worry % divisible == 0 { true_monkey } else { false_monkey }
-        });
+fn part_two(monkeys: &[Monkey]) -> BigUint {
+    monkey_business(&Engine::part_two(monkeys).run(monkeys, &mut []).expect("Should have played"), 2)
+}
 
-        Self {
-            id,
-            items,
-            operation,
-            action,
-            inspected_count: 0,
-            modulus: divisible
+fn main() {
+    let data = load_data();
+    let monkeys = parse_troop(&data).expect("Should have parsed");
+    if env::args().any(|arg| arg == "--print") {
+        let troop: Vec<String> = monkeys.iter().map(|monkey| monkey.to_string()).collect();
+        print!("{}", troop.join("\n"));
+        return;
+    }
+    let args: Vec<String> = env::args().skip(1).collect();
+    let option = |name: &str| args.iter().position(|arg| arg == name).and_then(|pos| args.get(pos + 1));
+    // --relief none, lcm, div:K, mod:M, or custom sqrt or sub:K, for the modes below
+    let relief = match option("--relief").map(|spec| spec.split_once(':').unwrap_or((spec, ""))) {
+        None | Some(("none", _)) => Relief::None,
+        Some(("lcm", _)) => Relief::Modulo(divisor_lcm(&monkeys)),
+        Some(("div", k)) => Relief::DivideBy(k.parse().ok().filter(|&k| k > 0).expect("Divide by a positive number")),
+        Some(("mod", m)) => Relief::Modulo(m.parse().ok().filter(|&m| m > 0).expect("Modulo a positive number")),
+        Some(("sqrt", _)) => Relief::Custom(Rc::new(u64::isqrt)),
+        Some(("sub", k)) => {
+            let k: u64 = k.parse().expect("Subtract a number");
+            Relief::Custom(Rc::new(move |worry| worry.saturating_sub(k)))
         }
+        Some((other, _)) => panic!("Unknown relief {:?}", other),
+    };
+    // --top K multiplies the K busiest monkeys' counts for monkey business
+    let top = option("--top").map_or(2, |k| k.parse().expect("Top how many?"));
+    // --exact N plays N rounds with exact worry levels
+    if let Some(rounds) = option("--exact") {
+        let counts = exact::exact_inspections(&monkeys, rounds.parse().expect("Rounds is a number"), relief).expect("Should have played");
+        for (monkey, count) in counts.iter().enumerate() {
+            println!("Monkey {} inspected items {} times.", monkey, count);
+        }
+        println!("Monkey business: {}", monkey_business(&counts, top));
+        return;
     }
-
-    fn inspect_item(&mut self, worry: u64, part_one: bool, modulus: u64) -> (u64, usize) {
-        self.inspected_count += 1;
-        let new_worry = (self.operation)(worry, part_one, modulus);
-        let next_monkey = (self.action)(new_worry);
-        (new_worry, next_monkey)
+    // --rounds N skips ahead once each item starts going round in circles
+    if let Some(rounds) = option("--rounds") {
+        let counts = skip::inspections_after(&monkeys, rounds.parse().expect("Rounds is a number"), &relief).expect("Should have played");
+        for (monkey, count) in counts.iter().enumerate() {
+            println!("Monkey {} inspected items {} times.", monkey, count);
+        }
+        println!("Monkey business: {}", monkey_business(&counts, top));
+        return;
     }
-}
-
-fn part_one(monkeys: &mut Vec<Monkey>) -> usize {
-    let monkey_count = monkeys.len();
-    for _ in 0..20 {
-        for ix in 0..monkey_count {
-            while !monkeys.get(ix).unwrap().items.is_empty() {
-                let item = monkeys.get_mut(ix).unwrap().items.pop_front().unwrap();
-                let (new_item, next_monkey) = monkeys.get_mut(ix).unwrap().inspect_item(item, true, 1);
-                monkeys.get_mut(next_monkey).unwrap().items.push_back(new_item);
-            }
+    // --play N plays every one of N rounds
+    if let Some(rounds) = option("--play") {
+        let engine = Engine { rounds: rounds.parse().expect("Rounds is a number"), relief };
+        let counts = engine.run(&monkeys, &mut []).expect("Should have played");
+        for (monkey, count) in counts.iter().enumerate() {
+            println!("Monkey {} inspected items {} times.", monkey, count);
         }
+        println!("Monkey business: {}", monkey_business(&counts, top));
+        return;
     }
-    let max_monkey = monkeys.iter().max_by_key(|m| m.inspected_count).unwrap();
-    let max_monkey2 = monkeys.iter().max_by_key(|m| if m.id == max_monkey.id { 0 } else { m.inspected_count }).unwrap();
-    max_monkey.inspected_count * max_monkey2.inspected_count
-}
-
-fn part_two(monkeys: &mut Vec<Monkey>, modulus: u64) -> usize {
-    let monkey_count = monkeys.len();
-    for _ in 0..10000 {
-        for ix in 0..monkey_count {
-            while !monkeys.get(ix).unwrap().items.is_empty() {
-                let item = monkeys.get_mut(ix).unwrap().items.pop_front().unwrap();
-                let (new_item, next_monkey) = monkeys.get_mut(ix).unwrap().inspect_item(item, false, modulus);
-                monkeys.get_mut(next_monkey).unwrap().items.push_back(new_item);
+    // --check N compares the u64 shortcuts with exact worry levels over N rounds
+    if let Some(rounds) = option("--check") {
+        match exact::check_shortcuts(&monkeys, rounds.parse().expect("Rounds is a number")) {
+            Ok(()) => println!("Shortcuts agree with exact worry levels"),
+            Err(e) => println!("{}", e),
+        }
+        return;
+    }
+    // --trace follows every item through part one's rounds, optionally with --csv FILE
+    // and --journey ITEM
+    if args.iter().any(|arg| arg == "--trace") {
+        let mut tracer = Tracer::new(&monkeys);
+        Engine::part_one().run(&monkeys, &mut [&mut tracer]).expect("Should have played");
+        println!("{}", tracer.summaries.join("\n"));
+        if let Some(path) = option("--csv") {
+            fs::write(path, tracer.csv()).expect("Should have written");
+        }
+        if let Some(item) = option("--journey") {
+            for hop in tracer.journey(item.parse().expect("Items are numbered")) {
+                println!("Round {}: monkey {} took {} to {} and threw it to monkey {}", hop.round, hop.from, hop.before, hop.after, hop.to);
             }
         }
+        return;
     }
-    let max_monkey = monkeys.iter().max_by_key(|m| m.inspected_count).unwrap();
-    let max_monkey2 = monkeys.iter().max_by_key(|m| if m.id == max_monkey.id { 0 } else { m.inspected_count }).unwrap();
-    max_monkey.inspected_count * max_monkey2.inspected_count
-}
-
-fn main() {
-    let data = load_data();
-    let mut monkeys: Vec<Monkey> = data.split("\n\n").enumerate().map(|(ix, chunk)| Monkey::from_input(chunk, ix)).collect();
-    let monkeyness = part_one(&mut monkeys);
+    let monkeyness = part_one(&monkeys);
     println!("Part one: {}", monkeyness);
-    let mut monkeys: Vec<Monkey> = data.split("\n\n").enumerate().map(|(ix, chunk)| Monkey::from_input(chunk, ix)).collect();
-    let modulus = monkeys.iter().map(|m| m.modulus).
-        reduce(|acc, modls| lcm(acc,modls)).unwrap();
-    println!("{:?}", monkeys);
-    let monkeyness = part_two(&mut monkeys, modulus);
+    let monkeyness = part_two(&monkeys);
     println!("Part two: {}", monkeyness);
 }
 
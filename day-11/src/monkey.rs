@@ -0,0 +1,185 @@
+use std::fmt;
+use std::rc::Rc;
+use std::collections::VecDeque;
+use num::integer::lcm;
+use regex::{Captures, Regex};
+use crate::expr::Expr;
+
+// What happens to a worry level once a monkey has finished with an item
+#[derive(Clone)]
+pub enum Relief {
+    // Part one's relief that the item wasn't damaged
+    DivideBy(u64),
+    // Keeps worry levels small without changing any test, given a multiple of every divisor
+    // and operations that only add and multiply (see `suits`)
+    Modulo(u64),
+    None,
+    // Anything else, which may capture its own settings
+    Custom(Rc<dyn Fn(u64) -> u64>),
+}
+impl Relief {
+    pub fn apply(&self, worry: u64) -> u64 {
+        match self {
+            Self::DivideBy(k) => worry / k,
+            Self::Modulo(m) => worry % m,
+            Self::None => worry,
+            Self::Custom(f) => f(worry),
+        }
+    }
+
+    // Working modulo anything gives wrong answers once an operation divides or subtracts
+    pub fn suits(&self, monkeys: &[Monkey]) -> Result<(), String> {
+        if let Self::Modulo(m) = self {
+            if let Some(monkey) = monkeys.iter().find(|monkey| !monkey.operation.is_polynomial()) {
+                return Err(format!("Monkey {}'s operation {} uses - or /, so worry levels can't be kept modulo {}", monkey.id, monkey.operation, m));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Monkey {
+    pub id: usize,
+    pub items: VecDeque<u64>,
+    pub operation: Expr,
+    // Throws to if_true when the new worry is a multiple of divisor, else to if_false
+    pub divisor: u64,
+    pub if_true: usize,
+    pub if_false: usize,
+    pub inspected_count: u64,
+}
+impl Monkey {
+    pub fn target(&self, worry: u64) -> usize {
+        if worry.is_multiple_of(self.divisor) { self.if_true } else { self.if_false }
+    }
+
+    // The new worry level and who it's thrown to
+    pub fn inspect(&self, worry: u64, relief: &Relief) -> Result<(u64, usize), String> {
+        let new_worry = self.operation.eval(&worry)
+            .map_err(|e| format!("Monkey {} with worry level {}: {}", self.id, worry, e))?;
+        let new_worry = relief.apply(new_worry);
+        Ok((new_worry, self.target(new_worry)))
+    }
+
+    pub fn inspect_item(&mut self, worry: u64, relief: &Relief) -> Result<(u64, usize), String> {
+        self.inspected_count += 1;
+        self.inspect(worry, relief)
+    }
+}
+
+pub fn divisor_lcm(monkeys: &[Monkey]) -> u64 {
+    monkeys.iter().map(|monkey| monkey.divisor).reduce(lcm).unwrap_or(1)
+}
+
+// Anything that wants to watch the monkeys play
+pub trait Observer {
+    // An item's worry level went from `before` to `after`, and it was thrown
+    fn thrown(&mut self, from: usize, to: usize, before: u64, after: u64);
+    fn round_over(&mut self, monkeys: &[Monkey]);
+}
+
+// Each monkey in turn inspects and throws everything it's holding
+pub fn play_round(monkeys: &mut [Monkey], relief: &Relief, observers: &mut [&mut dyn Observer]) -> Result<(), String> {
+    for ix in 0..monkeys.len() {
+        while let Some(item) = monkeys[ix].items.pop_front() {
+            let (new_item, next_monkey) = monkeys[ix].inspect_item(item, relief)?;
+            for observer in observers.iter_mut() {
+                observer.thrown(ix, next_monkey, item, new_item);
+            }
+            monkeys[next_monkey].items.push_back(new_item);
+        }
+    }
+    for observer in observers.iter_mut() {
+        observer.round_over(monkeys);
+    }
+    Ok(())
+}
+
+// Back in the puzzle's own format
+impl fmt::Display for Monkey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let items: Vec<String> = self.items.iter().map(|item| item.to_string()).collect();
+        writeln!(f, "Monkey {}:", self.id)?;
+        writeln!(f, "  Starting items: {}", items.join(", "))?;
+        writeln!(f, "  Operation: new = {}", self.operation)?;
+        writeln!(f, "  Test: divisible by {}", self.divisor)?;
+        writeln!(f, "    If true: throw to monkey {}", self.if_true)?;
+        writeln!(f, "    If false: throw to monkey {}", self.if_false)
+    }
+}
+
+// Monkeys must be numbered 0 to n - 1, in any order, and only throw to other monkeys.
+// Whitespace within and around lines is free; blank lines separate monkeys.
+pub fn parse_troop(data: &str) -> Result<Vec<Monkey>, String> {
+    let header = Regex::new(r"^Monkey\s+(\d+)\s*:$").unwrap();
+    let items = Regex::new(r"^Starting\s+items\s*:\s*(.*)$").unwrap();
+    let operation = Regex::new(r"^Operation\s*:\s*new\s*=\s*(.+)$").unwrap();
+    let test = Regex::new(r"^Test\s*:\s*divisible\s+by\s+(\d+)$").unwrap();
+    let if_true = Regex::new(r"^If\s+true\s*:\s*throw\s+to\s+monkey\s+(\d+)$").unwrap();
+    let if_false = Regex::new(r"^If\s+false\s*:\s*throw\s+to\s+monkey\s+(\d+)$").unwrap();
+
+    let mut chunks: Vec<Vec<(usize, &str)>> = vec![];
+    let mut in_chunk = false;
+    for (ix, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            in_chunk = false;
+            continue;
+        }
+        if !in_chunk {
+            chunks.push(vec![]);
+            in_chunk = true;
+        }
+        chunks.last_mut().unwrap().push((ix + 1, line));
+    }
+
+    let mut monkeys = vec![];
+    for chunk in chunks {
+        let mut lines = chunk.iter();
+        let mut field = |rex: &Regex, what: &str| -> Result<String, String> {
+            let &(line_no, line) = lines.next().ok_or(format!("Monkey ending at line {} has no {}", chunk.last().unwrap().0, what))?;
+            let caps: Captures = rex.captures(line).ok_or(format!("line {}: expected {}, got {:?}", line_no, what, line))?;
+            Ok(caps[1].to_string())
+        };
+        let number = |text: String, what: &str| text.parse::<usize>().map_err(|_| format!("Bad {} {:?}", what, text));
+
+        let id = number(field(&header, "Monkey N:")?, "monkey id")?;
+        let item_list = field(&items, "starting items")?;
+        let items = item_list.split(',').map(str::trim).filter(|item| !item.is_empty())
+            .map(|item| item.parse::<u64>().map_err(|_| format!("Monkey {}: bad item {:?}", id, item)))
+            .collect::<Result<VecDeque<u64>, String>>()?;
+        // Caught here, like a constant divisor of 0, rather than on the first inspection
+        let operation = Expr::parse(&field(&operation, "operation")?)
+            .map_err(|e| format!("line {}: monkey {}'s operation: {}", chunk[2].0, id, e))?;
+        let divisor = field(&test, "test")?.parse::<u64>().map_err(|_| format!("Monkey {}: bad divisor", id))?;
+        if divisor == 0 {
+            return Err(format!("Monkey {}: can't test for divisibility by 0", id));
+        }
+        let if_true = number(field(&if_true, "true target")?, "target")?;
+        let if_false = number(field(&if_false, "false target")?, "target")?;
+        if let Some(&(line_no, line)) = lines.next() {
+            return Err(format!("line {}: unexpected {:?}", line_no, line));
+        }
+        monkeys.push(Monkey { id, items, operation, divisor, if_true, if_false, inspected_count: 0 });
+    }
+
+    monkeys.sort_by_key(|monkey| monkey.id);
+    for (ix, monkey) in monkeys.iter().enumerate() {
+        if ix > 0 && monkeys[ix - 1].id == monkey.id {
+            return Err(format!("There are two monkey {}s", monkey.id));
+        }
+        if monkey.id != ix {
+            return Err(format!("There's no monkey {}", ix));
+        }
+        for target in [monkey.if_true, monkey.if_false] {
+            if target >= monkeys.len() {
+                return Err(format!("Monkey {} throws to monkey {}, which doesn't exist", monkey.id, target));
+            }
+            if target == monkey.id {
+                return Err(format!("Monkey {} throws to itself", monkey.id));
+            }
+        }
+    }
+    Ok(monkeys)
+}
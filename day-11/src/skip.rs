@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use crate::monkey::{Monkey, Relief};
+
+// Items never affect each other, so each can be followed alone. At the start of a round
+// an item is just (monkey, worry); once that repeats, so does everything in between, and
+// the rest of the rounds can be counted rather than played. Relief that doesn't bound
+// worry levels may never repeat, and then this is no quicker than playing every round.
+pub fn inspections_after(monkeys: &[Monkey], rounds: u64, relief: &Relief) -> Result<Vec<u64>, String> {
+    relief.suits(monkeys)?;
+    let mut counts = vec![0; monkeys.len()];
+    for (ix, monkey) in monkeys.iter().enumerate() {
+        for &worry in &monkey.items {
+            follow(monkeys, (ix, worry), rounds, relief, &mut counts)?;
+        }
+    }
+    Ok(counts)
+}
+
+fn follow(monkeys: &[Monkey], start: (usize, u64), rounds: u64, relief: &Relief, counts: &mut [u64]) -> Result<(), String> {
+    let mut seen: HashMap<(usize, u64), usize> = HashMap::new();
+    // Which monkeys inspected the item, round by round
+    let mut inspections: Vec<Vec<usize>> = vec![];
+    let mut state = start;
+    while (inspections.len() as u64) < rounds {
+        if let Some(&first) = seen.get(&state) {
+            let period = &inspections[first..];
+            let left = rounds - inspections.len() as u64;
+            let (laps, rest) = (left / period.len() as u64, (left % period.len() as u64) as usize);
+            for (round, inspected) in period.iter().enumerate() {
+                let times = laps + (round < rest) as u64;
+                for &monkey in inspected {
+                    counts[monkey] += times;
+                }
+            }
+            return Ok(());
+        }
+        seen.insert(state, inspections.len());
+
+        // Thrown to a later monkey, it's inspected again this round
+        let (mut monkey, mut worry) = state;
+        let mut inspected = vec![];
+        loop {
+            let (new_worry, next_monkey) = monkeys[monkey].inspect(worry, relief)?;
+            inspected.push(monkey);
+            counts[monkey] += 1;
+            let round_over = next_monkey < monkey;
+            (monkey, worry) = (next_monkey, new_worry);
+            if round_over {
+                break;
+            }
+        }
+        inspections.push(inspected);
+        state = (monkey, worry);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::engine::Engine;
+    use crate::monkey::{divisor_lcm, parse_troop, Relief};
+    use super::inspections_after;
+
+    fn skips_like_brute_force(path: &str) {
+        let monkeys = parse_troop(&fs::read_to_string(path).expect("Should have read")).expect("Should have parsed");
+        let skipped = inspections_after(&monkeys, 10000, &Relief::Modulo(divisor_lcm(&monkeys))).unwrap();
+        assert_eq!(skipped, Engine::part_two(&monkeys).run(&monkeys, &mut []).unwrap());
+    }
+
+    #[test]
+    fn skips_like_brute_force_on_sample() {
+        skips_like_brute_force("data/data-sample.txt");
+    }
+
+    #[test]
+    fn skips_like_brute_force_on_real_data() {
+        skips_like_brute_force("data/data-real.txt");
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+use crate::monkey::{Monkey, Observer};
+
+// One inspection of one item
+pub struct Hop {
+    pub round: usize,
+    pub item: usize,
+    pub from: usize,
+    pub to: usize,
+    pub before: u64,
+    pub after: u64,
+}
+
+// Items are numbered in the order they appear in the input. Monkeys always throw from
+// the front of their queue to the back of another's, so a copy of the queues holding
+// item numbers is enough to follow each one.
+pub struct Tracer {
+    pub round: usize,
+    pub holding: Vec<VecDeque<usize>>,
+    pub hops: Vec<Hop>,
+    pub summaries: Vec<String>,
+}
+impl Tracer {
+    pub fn new(monkeys: &[Monkey]) -> Self {
+        let mut next_item = 0..;
+        let holding = monkeys.iter().map(|monkey| monkey.items.iter().map(|_| next_item.next().unwrap()).collect()).collect();
+        Self { round: 0, holding, hops: vec![], summaries: vec![] }
+    }
+
+    pub fn journey(&self, item: usize) -> impl Iterator<Item = &Hop> {
+        self.hops.iter().filter(move |hop| hop.item == item)
+    }
+
+    pub fn csv(&self) -> String {
+        let mut out = String::from("round,item,from,to,before,after\n");
+        for hop in &self.hops {
+            out.push_str(&format!("{},{},{},{},{},{}\n", hop.round, hop.item, hop.from, hop.to, hop.before, hop.after));
+        }
+        out
+    }
+}
+impl Observer for Tracer {
+    fn thrown(&mut self, from: usize, to: usize, before: u64, after: u64) {
+        let item = self.holding[from].pop_front().expect("Tracer should match the monkeys");
+        self.holding[to].push_back(item);
+        self.hops.push(Hop { round: self.round + 1, item, from, to, before, after });
+    }
+
+    // As the puzzle shows it
+    fn round_over(&mut self, monkeys: &[Monkey]) {
+        self.round += 1;
+        let mut summary = format!("After round {}, the monkeys are holding items with these worry levels:\n", self.round);
+        for monkey in monkeys {
+            let items: Vec<String> = monkey.items.iter().map(|item| item.to_string()).collect();
+            summary.push_str(&format!("Monkey {}: {}\n", monkey.id, items.join(", ")));
+        }
+        self.summaries.push(summary);
+    }
+}